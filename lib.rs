@@ -18,16 +18,18 @@ pub mod native_mint {
     declare_id!("So11111111111111111111111111111111111111112");
 }
 
-pub mod config_feature {
-    pub mod withdraw_authority {
-        use anchor_lang::declare_id;
-        declare_id!("715Zjd5g9kmUMBNBLDQWtbwqCptUrnCaebUfqkEK19rT");
-    }
-
-    pub mod platform_authority {
-        use anchor_lang::declare_id;
-        declare_id!("715Zjd5g9kmUMBNBLDQWtbwqCptUrnCaebUfqkEK19rT");
-    }
+/// `msg!` for diagnostics (buyback and early bird traces, fee-state and trade dumps,
+/// launch, identity and claim details); events carry the data clients rely on.
+/// Compiled out unless the `verbose-logs` feature is on; devnet builds enable it.
+macro_rules! verbose_msg {
+    ($($arg:tt)*) => {{
+        // Not every build declares the feature, so don't let the cfg check warn
+        #[allow(unexpected_cfgs)]
+        let enabled = cfg!(feature = "verbose-logs");
+        if enabled {
+            msg!($($arg)*);
+        }
+    }};
 }
 
 #[program]
@@ -42,6 +44,7 @@ pub mod yoink {
         ctx.accounts.holder_stats.fees_claimed = 0;
         ctx.accounts.holder_stats.entry_position = 0; // Will be set on first buy
         ctx.accounts.holder_stats.total_volume = 0;
+        ctx.accounts.holder_stats.version = HolderStats::VERSION;
 
         Ok(())
     }
@@ -61,6 +64,7 @@ pub mod yoink {
         ctx.accounts.global.authority = *ctx.accounts.user.key;
         ctx.accounts.global.initialized = true;
         ctx.accounts.global.buybacks_enabled = true;
+        ctx.accounts.global.version = Global::VERSION;
         Ok(())
     }
 
@@ -81,7 +85,10 @@ pub mod yoink {
         buyback_params: BuybackParams,
         early_bird_enabled: bool,
         early_bird_cutoff: u64,
-        early_bird_min_buy_sol: u64
+        early_bird_min_buy_sol: u64,
+        max_override_fee_basis_points: u64,
+        min_override_platform_fee_share: u64,
+        referral_fee_share: u64
     ) -> Result<()> {
         require!(ctx.accounts.global.initialized, HorseFunError::NotInitialized);
         require_keys_eq!(
@@ -90,30 +97,9 @@ pub mod yoink {
             HorseFunError::NotAuthorized
         );
 
-        // Validate fee shares add up to 100%
-        require!(
-            creator_fee_share + platform_fee_share + treasury_fee_share + early_bird_fee_share ==
-                10000,
-            HorseFunError::InvalidFeeShares
-        );
+        ctx.accounts.global.check_params_unlocked()?;
 
-        ctx.accounts.global.fee_recipient = fee_recipient;
-        ctx.accounts.global.initial_virtual_token_reserves = initial_virtual_token_reserves;
-        ctx.accounts.global.initial_virtual_sol_reserves = initial_virtual_sol_reserves;
-        ctx.accounts.global.initial_real_token_reserves = initial_real_token_reserves;
-        ctx.accounts.global.token_total_supply = token_total_supply;
-        ctx.accounts.global.fee_basis_points = fee_basis_points;
-        ctx.accounts.global.creator_fee_share = creator_fee_share;
-        ctx.accounts.global.platform_fee_share = platform_fee_share;
-        ctx.accounts.global.treasury_fee_share = treasury_fee_share;
-        ctx.accounts.global.early_bird_fee_share = early_bird_fee_share;
-        ctx.accounts.global.buybacks_enabled = buybacks_enabled;
-        ctx.accounts.global.buyback_params = buyback_params;
-        ctx.accounts.global.early_bird_enabled = early_bird_enabled;
-        ctx.accounts.global.early_bird_cutoff = early_bird_cutoff;
-        ctx.accounts.global.early_bird_min_buy_sol = early_bird_min_buy_sol;
-
-        emit_cpi!(SetParamsEvent {
+        let params = GlobalParams {
             fee_recipient,
             initial_virtual_token_reserves,
             initial_virtual_sol_reserves,
@@ -123,10 +109,21 @@ pub mod yoink {
             creator_fee_share,
             platform_fee_share,
             treasury_fee_share,
+            early_bird_fee_share,
             buybacks_enabled,
-        });
+            buyback_params,
+            early_bird_enabled,
+            early_bird_cutoff,
+            early_bird_min_buy_sol,
+            max_override_fee_basis_points,
+            min_override_platform_fee_share,
+            referral_fee_share,
+            ..ctx.accounts.global.params()
+        };
+        params.validate()?;
+        ctx.accounts.global.apply_params(&params);
 
-        emit!(SetParamsEvent {
+        let event = SetParamsEvent {
             fee_recipient,
             initial_virtual_token_reserves,
             initial_virtual_sol_reserves,
@@ -136,1608 +133,3574 @@ pub mod yoink {
             creator_fee_share,
             platform_fee_share,
             treasury_fee_share,
+            early_bird_fee_share,
             buybacks_enabled,
-        });
+            buyback_params,
+            early_bird_enabled,
+            early_bird_cutoff,
+            early_bird_min_buy_sol,
+            max_override_fee_basis_points,
+            min_override_platform_fee_share,
+            referral_fee_share,
+            timestamp: Clock::get()?.unix_timestamp,
+        };
+
+        emit_cpi!(event.clone());
+        emit!(event);
 
         Ok(())
     }
 
-    /// Creates a new coin and bonding curve.
-    pub fn create(
-        ctx: Context<Create>,
-        name: String,
-        symbol: String,
-        uri: String,
-        streamer_id: Option<String>
+    /// Sets the fee recipient, trading fee, fee split and override/referral bounds.
+    pub fn set_fee_config(
+        ctx: Context<SetParams>,
+        fee_recipient: Pubkey,
+        fee_basis_points: u64,
+        creator_fee_share: u64,
+        platform_fee_share: u64,
+        treasury_fee_share: u64,
+        early_bird_fee_share: u64,
+        max_override_fee_basis_points: u64,
+        min_override_platform_fee_share: u64,
+        referral_fee_share: u64
     ) -> Result<()> {
-        msg!("Creating new token:");
-        msg!(" - Creator Wallet: {}", ctx.accounts.user.key());
-        msg!(" - Mint Address: {}", ctx.accounts.mint.key());
+        require!(ctx.accounts.global.initialized, HorseFunError::NotInitialized);
+        require_keys_eq!(
+            ctx.accounts.user.key(),
+            ctx.accounts.global.authority,
+            HorseFunError::NotAuthorized
+        );
+        ctx.accounts.global.check_params_unlocked()?;
+
+        let mut params = ctx.accounts.global.params();
+        params.fee_recipient = fee_recipient;
+        params.fee_basis_points = fee_basis_points;
+        params.creator_fee_share = creator_fee_share;
+        params.platform_fee_share = platform_fee_share;
+        params.treasury_fee_share = treasury_fee_share;
+        params.early_bird_fee_share = early_bird_fee_share;
+        params.max_override_fee_basis_points = max_override_fee_basis_points;
+        params.min_override_platform_fee_share = min_override_platform_fee_share;
+        params.referral_fee_share = referral_fee_share;
+        params.validate_fee_config()?;
+        ctx.accounts.global.apply_params(&params);
+
+        let event = FeeConfigSetEvent {
+            fee_recipient,
+            fee_basis_points,
+            creator_fee_share,
+            platform_fee_share,
+            treasury_fee_share,
+            early_bird_fee_share,
+            max_override_fee_basis_points,
+            min_override_platform_fee_share,
+            referral_fee_share,
+            timestamp: Clock::get()?.unix_timestamp,
+        };
 
-        // Log streamer ID details
-        match &streamer_id {
-            Some(id) => {
-                msg!(" - Streamer ID provided: {}", id);
-                require!(!id.is_empty() && id.len() <= 50, HorseFunError::InvalidStreamerId);
-                msg!(" - Streamer ID validation passed");
-            }
-            None => msg!(" - No Streamer ID provided, using wallet-only verification"),
-        }
+        emit_cpi!(event.clone());
+        emit!(event);
 
-        // initialize the bonding curve parameters
-        ctx.accounts.bonding_curve.virtual_token_reserves =
-            ctx.accounts.global.initial_virtual_token_reserves;
-        ctx.accounts.bonding_curve.virtual_sol_reserves =
-            ctx.accounts.global.initial_virtual_sol_reserves;
-        ctx.accounts.bonding_curve.real_token_reserves =
-            ctx.accounts.global.initial_real_token_reserves;
-        ctx.accounts.bonding_curve.real_sol_reserves = 0;
-        ctx.accounts.bonding_curve.token_total_supply = ctx.accounts.global.token_total_supply;
-        ctx.accounts.bonding_curve.circulating_supply = ctx.accounts.global.token_total_supply;
-        ctx.accounts.bonding_curve.complete = false;
-        ctx.accounts.bonding_curve.total_burned_supply = 0;
-        ctx.accounts.bonding_curve.total_treasury_spent = 0;
+        Ok(())
+    }
 
-        // Set creator info
-        ctx.accounts.bonding_curve.creator_wallet = ctx.accounts.user.key();
-        ctx.accounts.bonding_curve.creator_streamer_id = streamer_id;
-        ctx.accounts.bonding_curve.creator_fee_pool = 0;
-        ctx.accounts.bonding_curve.treasury_fee_pool = 0;
-        ctx.accounts.bonding_curve.total_fees_accrued = 0;
-        ctx.accounts.bonding_curve.total_treasury_fees_accrued = 0;
-        ctx.accounts.bonding_curve.ema_lot_price = 0;
+    /// Toggles buybacks and sets the Global buyback defaults snapshotted by new curves.
+    pub fn set_buyback_params(
+        ctx: Context<SetParams>,
+        buybacks_enabled: bool,
+        buyback_params: BuybackParams
+    ) -> Result<()> {
+        require!(ctx.accounts.global.initialized, HorseFunError::NotInitialized);
+        require_keys_eq!(
+            ctx.accounts.user.key(),
+            ctx.accounts.global.authority,
+            HorseFunError::NotAuthorized
+        );
+        ctx.accounts.global.check_params_unlocked()?;
 
-        // Initialize early bird fields
-        ctx.accounts.bonding_curve.early_bird_pool = 0;
-        ctx.accounts.bonding_curve.total_buyers = 0;
-        ctx.accounts.bonding_curve.total_early_bird_fees_accrued = 0;
-        // Initialize with cutoff value - this will be decremented when early birds sell
-        ctx.accounts.bonding_curve.early_bird_valid_count = 0;
-        ctx.accounts.bonding_curve.early_bird_share_per_seat = 0; // Will be set when curve completes
+        let mut params = ctx.accounts.global.params();
+        params.buybacks_enabled = buybacks_enabled;
+        params.buyback_params = buyback_params;
+        params.buyback_params.validate()?;
+        ctx.accounts.global.apply_params(&params);
 
-        // set the metadata for the token
-        helpers::set_metadata(&ctx, name.clone(), symbol.clone(), uri.clone())?;
+        let event = BuybackParamsSetEvent {
+            buybacks_enabled,
+            buyback_params,
+            timestamp: Clock::get()?.unix_timestamp,
+        };
 
-        // mint tokens to the bonding curve
-        helpers::mint_to_bonding_curve(&ctx)?;
+        emit_cpi!(event.clone());
+        emit!(event);
 
-        // revoke the mint authority
-        helpers::revoke_mint_authority(&ctx)?;
+        Ok(())
+    }
 
-        emit_cpi!(CreateEvent {
-            name: name.clone(),
-            symbol: symbol.clone(),
-            uri: uri.clone(),
-            mint: ctx.accounts.mint.key(),
-            bonding_curve: ctx.accounts.bonding_curve.key(),
-            user: ctx.accounts.user.key(),
-        });
+    /// Sets the early bird toggle, default seat count and qualifying buy size.
+    pub fn set_early_bird_config(
+        ctx: Context<SetParams>,
+        early_bird_enabled: bool,
+        early_bird_cutoff: u64,
+        early_bird_min_buy_sol: u64
+    ) -> Result<()> {
+        require!(ctx.accounts.global.initialized, HorseFunError::NotInitialized);
+        require_keys_eq!(
+            ctx.accounts.user.key(),
+            ctx.accounts.global.authority,
+            HorseFunError::NotAuthorized
+        );
+        ctx.accounts.global.check_params_unlocked()?;
+
+        let mut params = ctx.accounts.global.params();
+        params.early_bird_enabled = early_bird_enabled;
+        params.early_bird_cutoff = early_bird_cutoff;
+        params.early_bird_min_buy_sol = early_bird_min_buy_sol;
+        params.validate_early_bird_config()?;
+        ctx.accounts.global.apply_params(&params);
+
+        let event = EarlyBirdConfigSetEvent {
+            early_bird_enabled,
+            early_bird_cutoff,
+            early_bird_min_buy_sol,
+            timestamp: Clock::get()?.unix_timestamp,
+        };
 
-        emit!(CreateEvent {
-            name,
-            symbol,
-            uri,
-            mint: ctx.accounts.mint.key(),
-            bonding_curve: ctx.accounts.bonding_curve.key(),
-            user: ctx.accounts.user.key(),
-        });
+        emit_cpi!(event.clone());
+        emit!(event);
 
         Ok(())
     }
 
-    /// Buys tokens from a bonding curve.
-    pub fn buy(mut ctx: Context<Buy>, amount: u64, max_sol_cost: u64) -> Result<()> {
-        // Log initial fee state
-        msg!("Buy: Fee state before trade:");
-        msg!(" - Creator Fee Pool: {}", ctx.accounts.bonding_curve.creator_fee_pool);
-        msg!(" - Treasury Fee Pool: {}", ctx.accounts.bonding_curve.treasury_fee_pool);
-        msg!(" - Total Creator Fees Accrued: {}", ctx.accounts.bonding_curve.total_fees_accrued);
-        msg!(
-            " - Total Treasury Fees Accrued: {}",
-            ctx.accounts.bonding_curve.total_treasury_fees_accrued
+    /// Sets the default reserves and supply for curves created without a preset.
+    pub fn set_curve_defaults(
+        ctx: Context<SetParams>,
+        initial_virtual_token_reserves: u64,
+        initial_virtual_sol_reserves: u64,
+        initial_real_token_reserves: u64,
+        token_total_supply: u64
+    ) -> Result<()> {
+        require!(ctx.accounts.global.initialized, HorseFunError::NotInitialized);
+        require_keys_eq!(
+            ctx.accounts.user.key(),
+            ctx.accounts.global.authority,
+            HorseFunError::NotAuthorized
         );
+        ctx.accounts.global.check_params_unlocked()?;
+
+        let mut params = ctx.accounts.global.params();
+        params.initial_virtual_token_reserves = initial_virtual_token_reserves;
+        params.initial_virtual_sol_reserves = initial_virtual_sol_reserves;
+        params.initial_real_token_reserves = initial_real_token_reserves;
+        params.token_total_supply = token_total_supply;
+        params.validate_curve_defaults()?;
+        // The default wallet cap can't outgrow the new default reserves
+        params.validate_launch_protection()?;
+        ctx.accounts.global.apply_params(&params);
+
+        let event = CurveDefaultsSetEvent {
+            initial_virtual_token_reserves,
+            initial_virtual_sol_reserves,
+            initial_real_token_reserves,
+            token_total_supply,
+            timestamp: Clock::get()?.unix_timestamp,
+        };
 
-        // Cap amount to available reserves
-        let available_amount = std::cmp::min(
-            amount,
-            ctx.accounts.bonding_curve.real_token_reserves
-        );
+        emit_cpi!(event.clone());
+        emit!(event);
 
-        // Calculate price for the capped amount
-        let sol_cost = ctx.accounts.bonding_curve.buy_quote(available_amount as u128);
-        let fee = ctx.accounts.global.get_fee(sol_cost);
+        Ok(())
+    }
 
-        // Check slippage
-        require!(sol_cost + fee <= max_sol_cost, HorseFunError::TooMuchSolRequired);
+    /// Sets the delay between `propose_params` and `execute_params`. A non-zero delay
+    /// disables the immediate Global setters (`set_params`, fee tiers, launch protection,
+    /// price band, ...) and can then only be lengthened here; shortening it would let
+    /// the authority skip the queue.
+    pub fn set_params_timelock(ctx: Context<SetParams>, timelock_secs: i64) -> Result<()> {
+        require!(ctx.accounts.global.initialized, HorseFunError::NotInitialized);
         require_keys_eq!(
-            ctx.accounts.associated_bonding_curve.mint,
-            ctx.accounts.mint.key(),
-            HorseFunError::MintDoesNotMatchBondingCurve
+            ctx.accounts.user.key(),
+            ctx.accounts.global.authority,
+            HorseFunError::NotAuthorized
+        );
+        let current = ctx.accounts.global.params_timelock_secs;
+        require!(
+            timelock_secs >= 0 && (current == 0 || timelock_secs >= current),
+            HorseFunError::InvalidParamsTimelock
         );
-        require!(!ctx.accounts.bonding_curve.complete, HorseFunError::BondingCurveComplete);
 
-        msg!(" - Amount: {}", amount);
-        msg!(" - virtual token reserves: {}", ctx.accounts.bonding_curve.virtual_token_reserves);
-        msg!(" - Real Token Reserves: {}", ctx.accounts.bonding_curve.real_token_reserves);
+        ctx.accounts.global.params_timelock_secs = timelock_secs;
 
-        // Update virtual reserves (these track the theoretical price curve, including fees)
-        ctx.accounts.bonding_curve.virtual_token_reserves -= available_amount;
-        ctx.accounts.bonding_curve.virtual_sol_reserves += sol_cost;
+        let event = ParamsTimelockSetEvent {
+            timelock_secs,
+            timestamp: Clock::get()?.unix_timestamp,
+        };
 
-        // Update real reserves (actual tokens and SOL in the curve)
-        ctx.accounts.bonding_curve.real_token_reserves -= available_amount;
-        // Only add the actual SOL cost to reserves, fees are tracked separately in fee pools
-        ctx.accounts.bonding_curve.real_sol_reserves += sol_cost;
+        emit_cpi!(event.clone());
+        emit!(event);
 
-        if ctx.accounts.bonding_curve.real_token_reserves == 0 {
-            ctx.accounts.bonding_curve.complete = true;
+        Ok(())
+    }
 
-            // ⭐ Calculate and cache equal share for early bird rewards
-            // This ensures all early birds get EXACTLY the same amount
-            let valid_count = ctx.accounts.bonding_curve.early_bird_valid_count;
-            if valid_count > 0 && ctx.accounts.bonding_curve.early_bird_pool > 0 {
-                ctx.accounts.bonding_curve.early_bird_share_per_seat =
-                    ctx.accounts.bonding_curve.early_bird_pool / valid_count;
-                msg!(
-                    "🐦 Early Bird rewards locked: {} valid seats, {} lamports per seat",
-                    valid_count,
-                    ctx.accounts.bonding_curve.early_bird_share_per_seat
-                );
-            }
+    /// Queues a full set of Global parameters, executable once the timelock has passed.
+    pub fn propose_params(ctx: Context<ProposeParams>, params: GlobalParams) -> Result<()> {
+        require!(ctx.accounts.global.initialized, HorseFunError::NotInitialized);
+        require_keys_eq!(
+            ctx.accounts.user.key(),
+            ctx.accounts.global.authority,
+            HorseFunError::NotAuthorized
+        );
+        params.validate()?;
+
+        let now = Clock::get()?.unix_timestamp;
+        let eta = now.saturating_add(ctx.accounts.global.params_timelock_secs);
+        let pending_params = &mut ctx.accounts.pending_params;
+        pending_params.params = params;
+        pending_params.proposed_by = ctx.accounts.user.key();
+        pending_params.proposed_at = now;
+        pending_params.eta = eta;
+        pending_params.version = PendingParams::VERSION;
+
+        let event = ParamsProposedEvent {
+            current: ctx.accounts.global.params(),
+            proposed: params,
+            eta,
+            timestamp: now,
+        };
 
-            emit_cpi!(CompleteEvent {
-                mint: ctx.accounts.mint.key(),
-                user: ctx.accounts.user.key(),
-                bonding_curve: ctx.accounts.bonding_curve.key(),
-                timestamp: Clock::get()?.unix_timestamp,
-                early_bird_pool: ctx.accounts.bonding_curve.early_bird_pool,
-            });
+        emit_cpi!(event.clone());
+        emit!(event);
 
-            emit!(CompleteEvent {
-                mint: ctx.accounts.mint.key(),
-                user: ctx.accounts.user.key(),
-                bonding_curve: ctx.accounts.bonding_curve.key(),
-                timestamp: Clock::get()?.unix_timestamp,
-                early_bird_pool: ctx.accounts.bonding_curve.early_bird_pool,
-            });
-        }
+        Ok(())
+    }
 
-        // transfer the capped amount using the helper
-        helpers::transfer_tokens_from_bonding_curve_to_user(&ctx, available_amount)?;
+    /// Applies the queued parameters once their `eta` has passed.
+    pub fn execute_params(ctx: Context<ResolveParams>) -> Result<()> {
+        require!(ctx.accounts.global.initialized, HorseFunError::NotInitialized);
+        require_keys_eq!(
+            ctx.accounts.user.key(),
+            ctx.accounts.global.authority,
+            HorseFunError::NotAuthorized
+        );
+        let now = Clock::get()?.unix_timestamp;
+        require!(now >= ctx.accounts.pending_params.eta, HorseFunError::ParamsNotReady);
+
+        let params = ctx.accounts.pending_params.params;
+        params.validate()?;
+        let previous = ctx.accounts.global.params();
+        ctx.accounts.global.apply_params(&params);
+
+        let event = ParamsExecutedEvent {
+            previous,
+            applied: params,
+            timestamp: now,
+        };
 
-        // transfer the sol from the user to the bonding curve (only the actual cost, not including fee)
-        helpers::transfer_sol_from_user_to_bonding_curve(&ctx, sol_cost)?;
+        emit_cpi!(event.clone());
+        emit!(event);
 
-        // transfer the fee separately from user to fee recipient and update fee pools
-        helpers::transfer_sol_from_user_to_fee_recipient(&mut ctx, fee)?;
+        Ok(())
+    }
 
-        // Log final fee state
-        msg!("Buy: Fee state after trade:");
-        msg!(" - Creator Fee Pool: {}", ctx.accounts.bonding_curve.creator_fee_pool);
-        msg!(" - Treasury Fee Pool: {}", ctx.accounts.bonding_curve.treasury_fee_pool);
-        msg!(" - Early Bird Pool: {}", ctx.accounts.bonding_curve.early_bird_pool);
-        msg!(" - Total Creator Fees Accrued: {}", ctx.accounts.bonding_curve.total_fees_accrued);
-        msg!(
-            " - Total Treasury Fees Accrued: {}",
-            ctx.accounts.bonding_curve.total_treasury_fees_accrued
+    /// Drops the queued parameters without applying them.
+    pub fn cancel_params(ctx: Context<ResolveParams>) -> Result<()> {
+        require!(ctx.accounts.global.initialized, HorseFunError::NotInitialized);
+        require_keys_eq!(
+            ctx.accounts.user.key(),
+            ctx.accounts.global.authority,
+            HorseFunError::NotAuthorized
         );
 
-        // Update holder stats and track entry position for early bird rewards
-        ctx.accounts.holder_stats.current_balance = ctx.accounts.holder_stats.current_balance
-            .checked_add(available_amount)
-            .ok_or(HorseFunError::ArithmeticOverflow)?;
-        ctx.accounts.holder_stats.total_volume += sol_cost;
+        let event = ParamsCancelledEvent {
+            current: ctx.accounts.global.params(),
+            cancelled: ctx.accounts.pending_params.params,
+            timestamp: Clock::get()?.unix_timestamp,
+        };
 
-        // Track entry position if this is the first buy (for early bird rewards)
-        // Note: entry_position == 0 means first-time buyer
-        //       entry_position == u64::MAX means revoked/disqualified (sold before)
-        // Anti-Sybil: Requires minimum SOL buy amount to prevent 50-wallet gaming
-        if ctx.accounts.holder_stats.entry_position == 0 {
-            // Check if buy amount meets minimum threshold for Early Bird eligibility
-            if sol_cost >= ctx.accounts.global.early_bird_min_buy_sol {
-                ctx.accounts.bonding_curve.total_buyers += 1;
-                ctx.accounts.holder_stats.entry_position = ctx.accounts.bonding_curve.total_buyers;
+        emit_cpi!(event.clone());
+        emit!(event);
 
-                // Log early bird status
-                if
-                    ctx.accounts.global.early_bird_enabled &&
-                    ctx.accounts.holder_stats.entry_position <=
-                        ctx.accounts.global.early_bird_cutoff
-                {
-                    ctx.accounts.bonding_curve.early_bird_valid_count += 1;
-                    msg!(
-                        "🐦 Early Bird #{}/{}! User will earn rewards from future trades!",
-                        ctx.accounts.holder_stats.entry_position,
-                        ctx.accounts.global.early_bird_cutoff
-                    );
-                }
-            } else {
-                msg!(
-                    "⚠️ Buy amount {} lamports is below minimum {} lamports for Early Bird eligibility",
-                    sol_cost,
-                    ctx.accounts.global.early_bird_min_buy_sol
-                );
-            }
-        } else if ctx.accounts.holder_stats.entry_position == u64::MAX {
-            msg!("🚫 User is permanently disqualified from Early Bird rewards (sold previously)");
-        }
+        Ok(())
+    }
 
-        // Initialize buyback-related values with defaults (no buyback occurred yet)
-        let mut is_buyback = false;
-        let mut burn_amount = 0;
-        let mut price_lamports_per_token = 0;
+    /// Replaces the volume tier table used for trader fee discounts.
+    /// Tiers must be in ascending `min_volume` order with non-decreasing discounts.
+    /// Like the other fee setters this goes through `propose_params` once a timelock is set.
+    pub fn set_fee_tiers(ctx: Context<SetParams>, tiers: Vec<FeeTier>) -> Result<()> {
+        require!(ctx.accounts.global.initialized, HorseFunError::NotInitialized);
+        require_keys_eq!(
+            ctx.accounts.user.key(),
+            ctx.accounts.global.authority,
+            HorseFunError::NotAuthorized
+        );
+        ctx.accounts.global.check_params_unlocked()?;
+        require!(
+            tiers.len() <= MAX_FEE_TIERS && tiers.iter().all(|tier| tier.min_volume > 0),
+            HorseFunError::InvalidFeeTiers
+        );
 
-        if ctx.accounts.global.buybacks_enabled {
-            // ---- Dynamic (EMA + Backing) Buyback: lot-based, with verbose logs & safer caps ----
-            const DECIMALS: u32 = 6; // your mint decimals
-            const LOT_SIZE: u64 = (10u64).pow(DECIMALS); // 1 whole token
-
-            let gp = ctx.accounts.global.buyback_params;
-            let backing_mult_bps = gp.backing_mult_bps as u64;
-            let ema_drop_bps = gp.ema_drop_bps as u64;
-            let ema_alpha_bps = gp.ema_alpha_bps as u64;
-            let spend_bps = gp.spend_bps as u64;
-            let max_supply_bps = gp.max_supply_bps as u64;
-            let min_backing_lamports = gp.min_backing_lamports; // ⭐ DON'T override this later!
-            let max_burn_pct = gp.max_burn_percentage_bps as u64;
-
-            // Check if we've already hit the maximum burn limit (TOTAL cumulative burns, not per-tx)
-            let total_supply = ctx.accounts.bonding_curve.token_total_supply;
-            let already_burned = ctx.accounts.bonding_curve.total_burned_supply;
-            let current_burn_pct = if total_supply > 0 {
-                // Use u128 to prevent overflow on large token supplies
-                (((already_burned as u128) * 10_000) / (total_supply as u128)) as u64
-            } else {
-                0
-            };
+        let mut params = ctx.accounts.global.params();
+        params.fee_tiers = [FeeTier::default(); MAX_FEE_TIERS];
+        params.fee_tiers[..tiers.len()].copy_from_slice(&tiers);
+        GlobalParams::validate_fee_tiers(&params.fee_tiers)?;
+        ctx.accounts.global.apply_params(&params);
 
-            if current_burn_pct >= max_burn_pct {
-                msg!(
-                    "BB[LIMIT]: Max burn percentage reached: {}bps / {}bps ({}% / {}%)",
-                    current_burn_pct,
-                    max_burn_pct,
-                    current_burn_pct / 100,
-                    max_burn_pct / 100
-                );
-                msg!("BB[LIMIT]: Total burned: {} / {}", already_burned, total_supply);
-            } else {
-                msg!(
-                    "BB[burn-status]: {}/{}bps used ({}% / {}%)",
-                    current_burn_pct,
-                    max_burn_pct,
-                    current_burn_pct / 100,
-                    max_burn_pct / 100
-                );
+        let event = FeeTiersSetEvent {
+            tiers,
+            timestamp: Clock::get()?.unix_timestamp,
+        };
 
-                let vt = ctx.accounts.bonding_curve.virtual_token_reserves;
-                let vsol = ctx.accounts.bonding_curve.virtual_sol_reserves;
-                let rtok = ctx.accounts.bonding_curve.real_token_reserves;
-                let tpool = ctx.accounts.bonding_curve.treasury_fee_pool;
+        emit_cpi!(event.clone());
+        emit!(event);
 
-                msg!("BB[state]: vt={} vsol={} rtok={} tpool={}", vt, vsol, rtok, tpool);
+        Ok(())
+    }
 
-                if vt > 1 && tpool > 0 {
-                    // 1) Quote a *lot*, not 1 atomic unit
-                    let lot = LOT_SIZE.min(vt.saturating_sub(1));
-                    match ctx.accounts.bonding_curve.buy_quote_checked(lot) {
-                        None => {
-                            msg!("BB[skip]: unsafe lot quote (lot={} >= vT={})", lot, vt);
-                        }
-                        Some(market_lot) => {
-                            // 2) Compute backing per lot with treasury
-                            let backing_lot =
-                                ctx.accounts.bonding_curve.backing_per_lot_with_treasury(
-                                    lot,
-                                    tpool
-                                );
-
-                            // 3) Update EMA, then read it
-                            ctx.accounts.bonding_curve.update_ema_lot_price(
-                                market_lot,
-                                ema_alpha_bps
-                            );
-                            let ema_lot = ctx.accounts.bonding_curve.ema_lot_price;
-
-                            // 4) Build trigger thresholds
-                            let backing_thr = (((backing_lot as u128) *
-                                (backing_mult_bps as u128)) /
-                                10_000u128) as u64;
-                            let ema_thr = (((ema_lot as u128) * (ema_drop_bps as u128)) /
-                                10_000u128) as u64;
-                            let trigger_thr = backing_thr.max(ema_thr);
-
-                            msg!(
-                                "BB[chk]: lot={} mkt={} bkt={} ema={} thr={} tpool={}",
-                                lot,
-                                market_lot,
-                                backing_lot,
-                                ema_lot,
-                                trigger_thr,
-                                tpool
-                            );
-
-                            // 5) Trigger condition - balanced approach
-                            // Allow buybacks when price is below threshold AND
-                            // either backing is above a minimum OR the price drop is significant
-                            // NOTE: min_backing_lamports loaded from params above, dont override it here!
-                            let significant_drop = market_lot <= ema_lot / 2; // Price dropped by 50% or more from EMA
+    /// Sets the default anti-sniper launch window used by curves created without a preset.
+    /// Bounded like preset windows, against the default `initial_real_token_reserves`.
+    pub fn set_launch_protection(
+        ctx: Context<SetParams>,
+        launch_protection: LaunchProtection
+    ) -> Result<()> {
+        require!(ctx.accounts.global.initialized, HorseFunError::NotInitialized);
+        require_keys_eq!(
+            ctx.accounts.user.key(),
+            ctx.accounts.global.authority,
+            HorseFunError::NotAuthorized
+        );
+        ctx.accounts.global.check_params_unlocked()?;
 
-                            if
-                                market_lot <= trigger_thr &&
-                                (backing_lot >= min_backing_lamports.into() || significant_drop)
-                            {
-                                // Budget from treasury (lamports)
-                                let budget = (((tpool as u128) * (spend_bps as u128)) /
-                                    10_000u128) as u64;
-                                msg!("BB[budget]: spend_bps={} budget={}", spend_bps, budget);
-
-                                // Convert budget → tokens via CPMM inversion
-                                let mut amount =
-                                    ctx.accounts.bonding_curve.tokens_for_budget(budget);
-                                msg!("BB[size0]: tokens_for_budget -> {}", amount);
-
-                                // Cap to ≤10% of on-curve tokens; ensure it never floors to 0 when rtok>0
-                                let mut max_supply_buy = if rtok == 0 {
-                                    0
-                                } else {
-                                    (rtok.saturating_mul(max_supply_bps) / 10_000).max(1)
-                                };
-                                if max_supply_buy > rtok {
-                                    max_supply_buy = rtok;
-                                }
-
-                                msg!(
-                                    "BB[caps]: rtok={} cap_bps={} cap_tokens={}",
-                                    rtok,
-                                    max_supply_bps,
-                                    max_supply_buy
-                                );
-
-                                if max_supply_buy > 0 {
-                                    amount = amount.min(max_supply_buy);
-                                    msg!("BB[size1]: after cap -> {}", amount);
-                                } else {
-                                    msg!("BB[warn]: cap_tokens=0 (rtok=0), cannot buy");
-                                }
-
-                                // Guard rounding to zero → try minimum 1 atomic unit if affordable
-                                if amount == 0 {
-                                    match ctx.accounts.bonding_curve.buy_quote_checked(1) {
-                                        Some(min_cost) => {
-                                            msg!(
-                                                "BB[fallback-atomic]: min_cost={} tpool={}",
-                                                min_cost,
-                                                tpool
-                                            );
-                                            if tpool >= min_cost && max_supply_buy >= 1 {
-                                                amount = 1;
-                                                msg!("BB[fallback-atomic]: amount set to 1");
-                                            } else {
-                                                msg!(
-                                                    "BB[fallback-atomic]: insufficient tpool or cap<1"
-                                                );
-                                            }
-                                        }
-                                        None => msg!("BB[fallback-atomic]: unsafe quote(1)"),
-                                    }
-                                }
-
-                                // Optional second fallback: try one *lot* if affordable and within caps
-                                if amount == 0 && lot > 0 && max_supply_buy >= lot {
-                                    match ctx.accounts.bonding_curve.buy_quote_checked(lot) {
-                                        Some(lot_cost) => {
-                                            msg!(
-                                                "BB[fallback-lot]: lot={} cost={} tpool={}",
-                                                lot,
-                                                lot_cost,
-                                                tpool
-                                            );
-                                            if tpool >= lot_cost {
-                                                amount = lot.min(max_supply_buy);
-                                                msg!("BB[fallback-lot]: amount set to {}", amount);
-                                            } else {
-                                                msg!(
-                                                    "BB[fallback-lot]: insufficient tpool for lot"
-                                                );
-                                            }
-                                        }
-                                        None => msg!("BB[fallback-lot]: unsafe lot quote"),
-                                    }
-                                }
-
-                                if amount > 0 {
-                                    // Final quote & funds check
-                                    match ctx.accounts.bonding_curve.buy_quote_checked(amount) {
-                                        None => {
-                                            msg!(
-                                                "BB[skip]: unsafe quote for amount={} (vT={})",
-                                                amount,
-                                                ctx.accounts.bonding_curve.virtual_token_reserves
-                                            );
-                                        }
-                                        Some(bb_cost) => {
-                                            msg!(
-                                                "BB[quote]: amount={} bb_cost={} tpool={}",
-                                                amount,
-                                                bb_cost,
-                                                tpool
-                                            );
-                                            require!(
-                                                tpool >= bb_cost,
-                                                HorseFunError::InsufficientTreasuryFunds
-                                            );
-
-                                            // Snapshot pre-state for delta logs
-                                            let vtr0 =
-                                                ctx.accounts.bonding_curve.virtual_token_reserves;
-                                            let rtr0 =
-                                                ctx.accounts.bonding_curve.real_token_reserves;
-                                            let vsr0 =
-                                                ctx.accounts.bonding_curve.virtual_sol_reserves;
-                                            let rsr0 = ctx.accounts.bonding_curve.real_sol_reserves;
-                                            let tpool0 =
-                                                ctx.accounts.bonding_curve.treasury_fee_pool;
-
-                                            // Spend from treasury pool (book-keeping)
-                                            ctx.accounts.bonding_curve.treasury_fee_pool =
-                                                ctx.accounts.bonding_curve.treasury_fee_pool.saturating_sub(
-                                                    bb_cost
-                                                );
-
-                                            // Apply the same state transition as a buy:
-                                            ctx.accounts.bonding_curve.virtual_token_reserves =
-                                                ctx.accounts.bonding_curve.virtual_token_reserves.saturating_sub(
-                                                    amount
-                                                );
-                                            ctx.accounts.bonding_curve.virtual_sol_reserves =
-                                                ctx.accounts.bonding_curve.virtual_sol_reserves.saturating_add(
-                                                    bb_cost
-                                                );
-
-                                            ctx.accounts.bonding_curve.real_token_reserves =
-                                                ctx.accounts.bonding_curve.real_token_reserves.saturating_sub(
-                                                    amount
-                                                );
-                                            ctx.accounts.bonding_curve.real_sol_reserves =
-                                                ctx.accounts.bonding_curve.real_sol_reserves.saturating_add(
-                                                    bb_cost
-                                                );
-
-                                            // Intended burn sizing = full buyback size
-                                            burn_amount = amount;
-                                            let curve_ata_bal =
-                                                ctx.accounts.associated_bonding_curve.amount;
-                                            let mint_supply_before = ctx.accounts.mint.supply;
-
-                                            msg!(
-                                                "BB[burn-plan]: burn_amount={} curve_ata_bal={} mint_supply_before={}",
-                                                burn_amount,
-                                                curve_ata_bal,
-                                                mint_supply_before
-                                            );
-
-                                            // Safety guard
-                                            let burn_ok =
-                                                burn_amount > 0 && curve_ata_bal >= burn_amount;
-                                            msg!(
-                                                "BB[burn-guard]: curve_ata>=intended?={} ({}>=?)",
-                                                burn_ok,
-                                                curve_ata_bal
-                                            );
-
-                                            // ---- BURN ENABLED ----
-                                            if burn_ok {
-                                                {
-                                                    // Isolated scope to avoid borrow conflicts during CPI
-                                                    helpers::burn_from_curve_ata_on_buy(
-                                                        &ctx,
-                                                        burn_amount
-                                                    )?;
-                                                }
-
-                                                // IMPORTANT: do NOT subtract real_token_reserves again here.
-                                                // We already reduced it by `amount` when applying the buyback math above.
-                                                // Update circulating supply when tokens are burned
-                                                ctx.accounts.bonding_curve.circulating_supply =
-                                                    ctx.accounts.bonding_curve.circulating_supply.saturating_sub(
-                                                        burn_amount
-                                                    );
-                                            }
-
-                                            // track totals
-                                            ctx.accounts.bonding_curve.total_treasury_spent =
-                                                ctx.accounts.bonding_curve.total_treasury_spent.saturating_add(
-                                                    bb_cost
-                                                );
-
-                                            let mut burned: u64 = 0;
-                                            if burn_ok {
-                                                // burn already executed above
-                                                burned = burn_amount;
-                                                ctx.accounts.bonding_curve.total_burned_supply =
-                                                    ctx.accounts.bonding_curve.total_burned_supply.saturating_add(
-                                                        burn_amount
-                                                    );
-                                            }
-
-                                            let price_lpt = if amount > 0 {
-                                                bb_cost.saturating_div(amount)
-                                            } else {
-                                                0
-                                            };
-
-                                            // Update buyback variables for TradeEvent at end of function
-                                            is_buyback = true;
-                                            burn_amount = burned;
-                                            price_lamports_per_token = price_lpt;
-
-                                            // Note: TradeEvent will be emitted at end of function
-
-                                            // Delta log
-                                            msg!(
-                                                "BB[go]: amount={} cost={} new_tpool={}",
-                                                amount,
-                                                bb_cost,
-                                                ctx.accounts.bonding_curve.treasury_fee_pool
-                                            );
-                                            msg!(
-                                                "BB[delta]: Δvtok=-{} Δrtok=-{} Δvsol=+{} Δrsol=+{} Δtpool=-{}",
-                                                vtr0.saturating_sub(
-                                                    ctx.accounts.bonding_curve.virtual_token_reserves
-                                                ),
-                                                rtr0.saturating_sub(
-                                                    ctx.accounts.bonding_curve.real_token_reserves
-                                                ),
-                                                ctx.accounts.bonding_curve.virtual_sol_reserves.saturating_sub(
-                                                    vsr0
-                                                ),
-                                                ctx.accounts.bonding_curve.real_sol_reserves.saturating_sub(
-                                                    rsr0
-                                                ),
-                                                tpool0.saturating_sub(
-                                                    ctx.accounts.bonding_curve.treasury_fee_pool
-                                                )
-                                            );
-                                        }
-                                    }
-                                } else {
-                                    msg!("BB[skip]: amount=0 after sizing and fallbacks");
-                                }
-                            } else {
-                                msg!(
-                                    "BB[skip]: no trigger mkt={} thr={} bkt={}",
-                                    market_lot,
-                                    trigger_thr,
-                                    backing_lot
-                                );
-                            }
-                        }
-                    }
-                }
-            } // Close the else block for burn limit check
-        } else {
-            msg!("Buybacks are disabled globally");
-        }
-
-        // Calculate fee splits for this trade to emit in event
-        let (_, creator_fee_for_trade, _, _) = ctx.accounts.global.get_fee_splits(fee);
+        let mut params = ctx.accounts.global.params();
+        params.launch_protection = launch_protection;
+        params.validate_launch_protection()?;
+        ctx.accounts.global.apply_params(&params);
 
-        // 🔧 Calculate if this user is a valid early bird
-        let user_pos = ctx.accounts.holder_stats.entry_position;
-        let is_early_bird =
-            user_pos > 0 &&
-            user_pos != u64::MAX &&
-            user_pos <= ctx.accounts.global.early_bird_cutoff;
-
-        // Emit final TradeEvent with all information (including possible buyback)
-        let trade_event = TradeEvent {
-            user: ctx.accounts.user.key(),
-            sol_amount: sol_cost,
-            token_amount: available_amount,
-            is_buy: true,
-            mint: ctx.accounts.mint.key(),
+        let event = LaunchProtectionSetEvent {
+            launch_protection,
             timestamp: Clock::get()?.unix_timestamp,
-            virtual_sol_reserves: ctx.accounts.bonding_curve.virtual_sol_reserves,
-            virtual_token_reserves: ctx.accounts.bonding_curve.virtual_token_reserves,
-            circulating_supply: ctx.accounts.bonding_curve.circulating_supply,
-            real_token_reserves: ctx.accounts.bonding_curve.real_token_reserves,
-            real_sol_reserves: ctx.accounts.bonding_curve.real_sol_reserves,
-            creator_fee_pool: ctx.accounts.bonding_curve.creator_fee_pool,
-            treasury_fee_pool: ctx.accounts.bonding_curve.treasury_fee_pool,
-            total_fees_accrued: ctx.accounts.bonding_curve.total_fees_accrued,
-            total_treasury_fees_accrued: ctx.accounts.bonding_curve.total_treasury_fees_accrued,
-            creator_fee_amount: creator_fee_for_trade, // Fee earned by creator from THIS trade
-            fee_recipient: ctx.accounts.bonding_curve.creator_wallet, // Current creator (CTO-aware)
-            is_buyback,
-            burn_amount,
-            price_lamports_per_token,
-            total_burned_supply: ctx.accounts.bonding_curve.total_burned_supply,
-            total_treasury_spent: ctx.accounts.bonding_curve.total_treasury_spent,
-            early_bird_pool: ctx.accounts.bonding_curve.early_bird_pool,
-            total_early_bird_fees_accrued: ctx.accounts.bonding_curve.total_early_bird_fees_accrued,
-            user_position: ctx.accounts.holder_stats.entry_position,
-            user_balance: ctx.accounts.holder_stats.current_balance,
-            early_bird_cutoff: ctx.accounts.global.early_bird_cutoff,
-            total_buyers: ctx.accounts.bonding_curve.total_buyers,
-            early_bird_valid_count: ctx.accounts.bonding_curve.early_bird_valid_count,
-            is_early_bird,
         };
 
-        emit_cpi!(trade_event);
-        emit!(trade_event);
+        emit_cpi!(event.clone());
+        emit!(event);
 
         Ok(())
     }
 
-    /// Sells tokens into a bonding curve.
-    pub fn sell(mut ctx: Context<Sell>, amount: u64, min_sol_output: u64) -> Result<()> {
-        // Log initial fee state
-        msg!("Sell: Fee state before trade:");
-        msg!(" - Creator Fee Pool: {}", ctx.accounts.bonding_curve.creator_fee_pool);
-        msg!(" - Treasury Fee Pool: {}", ctx.accounts.bonding_curve.treasury_fee_pool);
-        msg!(" - Total Creator Fees Accrued: {}", ctx.accounts.bonding_curve.total_fees_accrued);
-        msg!(
-            " - Total Treasury Fees Accrued: {}",
-            ctx.accounts.bonding_curve.total_treasury_fees_accrued
+    /// Sets whether a creator dev-buy made inside `create` takes an early bird seat.
+    pub fn set_creator_buy_early_bird(
+        ctx: Context<SetParams>,
+        counts_as_early_bird: bool
+    ) -> Result<()> {
+        require!(ctx.accounts.global.initialized, HorseFunError::NotInitialized);
+        require_keys_eq!(
+            ctx.accounts.user.key(),
+            ctx.accounts.global.authority,
+            HorseFunError::NotAuthorized
         );
+        ctx.accounts.global.check_params_unlocked()?;
 
-        // Initialize buyback-related values with defaults (no buyback occurred yet)
-        let mut is_buyback = false;
-        let mut burn_amount = 0;
-        let mut price_lamports_per_token = 0;
+        let mut params = ctx.accounts.global.params();
+        params.creator_buy_counts_as_early_bird = counts_as_early_bird;
+        ctx.accounts.global.apply_params(&params);
 
-        let sol_output = ctx.accounts.bonding_curve.sell_quote(amount as u128);
-        let fee = ctx.accounts.global.get_fee(sol_output);
+        let event = CreatorBuyConfigSetEvent {
+            counts_as_early_bird,
+            timestamp: Clock::get()?.unix_timestamp,
+        };
 
-        // check that the sol cost is within the slippage tolerance
-        require!(
-            sol_output.saturating_sub(fee) >= min_sol_output,
-            HorseFunError::TooLittleSolReceived
-        );
+        emit_cpi!(event.clone());
+        emit!(event);
+
+        Ok(())
+    }
+
+    /// Sets how many slots a trade commitment must age before it can be revealed.
+    pub fn set_commit_reveal_delay(ctx: Context<SetParams>, min_slots: u64) -> Result<()> {
+        require!(ctx.accounts.global.initialized, HorseFunError::NotInitialized);
         require_keys_eq!(
-            ctx.accounts.associated_bonding_curve.mint,
-            ctx.accounts.mint.key(),
-            HorseFunError::MintDoesNotMatchBondingCurve
+            ctx.accounts.user.key(),
+            ctx.accounts.global.authority,
+            HorseFunError::NotAuthorized
         );
-        require!(!ctx.accounts.bonding_curve.complete, HorseFunError::BondingCurveComplete);
+        ctx.accounts.global.check_params_unlocked()?;
 
-        // update the bonding curve parameters (excluding fee)
-        ctx.accounts.bonding_curve.virtual_token_reserves =
-            ctx.accounts.bonding_curve.virtual_token_reserves.saturating_add(amount);
-        ctx.accounts.bonding_curve.real_token_reserves =
-            ctx.accounts.bonding_curve.real_token_reserves.saturating_add(amount);
-        ctx.accounts.bonding_curve.virtual_sol_reserves =
-            ctx.accounts.bonding_curve.virtual_sol_reserves.saturating_sub(sol_output);
-        // Only reduce by actual transfer amount (user receives sol_output - fee)
-        ctx.accounts.bonding_curve.real_sol_reserves =
-            ctx.accounts.bonding_curve.real_sol_reserves.saturating_sub(
-                sol_output.saturating_sub(fee)
-            );
+        let mut params = ctx.accounts.global.params();
+        params.commit_reveal_min_slots = min_slots;
+        ctx.accounts.global.apply_params(&params);
 
-        // transfer the tokens from the user to the bonding curve
-        helpers::transfer_tokens_from_user_to_bonding_curve(&ctx, amount)?;
+        let event = CommitRevealDelaySetEvent {
+            min_slots,
+            timestamp: Clock::get()?.unix_timestamp,
+        };
 
-        // Log final fee state
-        msg!("Sell: Fee state after trade:");
-        msg!(" - Creator Fee Pool: {}", ctx.accounts.bonding_curve.creator_fee_pool);
-        msg!(" - Treasury Fee Pool: {}", ctx.accounts.bonding_curve.treasury_fee_pool);
-        msg!(" - Early Bird Pool: {}", ctx.accounts.bonding_curve.early_bird_pool);
-        msg!(" - Total Creator Fees Accrued: {}", ctx.accounts.bonding_curve.total_fees_accrued);
-        msg!(
-            " - Total Treasury Fees Accrued: {}",
-            ctx.accounts.bonding_curve.total_treasury_fees_accrued
-        );
+        emit_cpi!(event.clone());
+        emit!(event);
 
-        // Calculate new balance by subtracting the tokens just sold from the current balance
-        // (Anchor's cached account might not reflect the latest transfer yet)
-        ctx.accounts.holder_stats.current_balance = ctx.accounts.holder_stats.current_balance
-            .checked_sub(amount)
-            .ok_or(HorseFunError::ArithmeticOverflow)?;
-        helpers::revoke_early_bird_status(
-            &mut ctx.accounts.holder_stats,
-            &mut ctx.accounts.bonding_curve,
-            &ctx.accounts.global
-        );
+        Ok(())
+    }
 
-        // Process buyback if enabled
-        if ctx.accounts.global.buybacks_enabled {
-            // ---- Dynamic (EMA + Backing) Buyback: lot-based, with verbose logs & safer caps ----
-            const DECIMALS: u32 = 6; // your mint decimals
-            const LOT_SIZE: u64 = (10u64).pow(DECIMALS); // 1 whole token
-
-            let gp = ctx.accounts.global.buyback_params;
-            let backing_mult_bps = gp.backing_mult_bps as u64;
-            let ema_drop_bps = gp.ema_drop_bps as u64;
-            let ema_alpha_bps = gp.ema_alpha_bps as u64;
-            let spend_bps = gp.spend_bps as u64;
-            let max_supply_bps = gp.max_supply_bps as u64;
-            let min_backing_lamports = gp.min_backing_lamports; // ⭐ DONT override this later!
-            let max_burn_pct = gp.max_burn_percentage_bps as u64;
-
-            // Check if we've already hit the maximum burn limit (TOTAL cumulative burns, not per-tx)
-            let total_supply = ctx.accounts.bonding_curve.token_total_supply;
-            let already_burned = ctx.accounts.bonding_curve.total_burned_supply;
-            let current_burn_pct = if total_supply > 0 {
-                // Use u128 to prevent overflow on large token supplies
-                (((already_burned as u128) * 10_000) / (total_supply as u128)) as u64
-            } else {
-                0
-            };
+    /// Sets the intra-slot price band enforced by the circuit breaker (0 disables it).
+    pub fn set_price_band(ctx: Context<SetParams>, price_band_bps: u16) -> Result<()> {
+        require!(ctx.accounts.global.initialized, HorseFunError::NotInitialized);
+        require_keys_eq!(
+            ctx.accounts.user.key(),
+            ctx.accounts.global.authority,
+            HorseFunError::NotAuthorized
+        );
+        ctx.accounts.global.check_params_unlocked()?;
 
-            if current_burn_pct >= max_burn_pct {
-                msg!(
-                    "SBB[LIMIT]: Max burn percentage reached: {}bps / {}bps ({}% / {}%)",
-                    current_burn_pct,
-                    max_burn_pct,
-                    current_burn_pct / 100,
-                    max_burn_pct / 100
-                );
-                msg!("SBB[LIMIT]: Total burned: {} / {}", already_burned, total_supply);
-            } else {
-                msg!(
-                    "SBB[burn-status]: {}/{}bps used ({}% / {}%)",
-                    current_burn_pct,
-                    max_burn_pct,
-                    current_burn_pct / 100,
-                    max_burn_pct / 100
-                );
+        let mut params = ctx.accounts.global.params();
+        params.price_band_bps = price_band_bps;
+        params.validate_price_band()?;
+        ctx.accounts.global.apply_params(&params);
 
-                let vt = ctx.accounts.bonding_curve.virtual_token_reserves;
-                let vsol = ctx.accounts.bonding_curve.virtual_sol_reserves;
-                let rtok = ctx.accounts.bonding_curve.real_token_reserves;
-                let tpool = ctx.accounts.bonding_curve.treasury_fee_pool;
+        let event = PriceBandSetEvent {
+            price_band_bps,
+            timestamp: Clock::get()?.unix_timestamp,
+        };
 
-                msg!("BB[state]: vt={} vsol={} rtok={} tpool={}", vt, vsol, rtok, tpool);
+        emit_cpi!(event.clone());
+        emit!(event);
 
-                if vt > 1 && tpool > 0 {
-                    // 1) Quote a *lot*, not 1 atomic unit
-                    let lot = LOT_SIZE.min(vt.saturating_sub(1));
-                    match ctx.accounts.bonding_curve.buy_quote_checked(lot) {
-                        None => {
-                            msg!("BB[skip]: unsafe lot quote (lot={} >= vT={})", lot, vt);
-                        }
-                        Some(market_lot) => {
-                            // 2) Compute backing per lot with treasury
-                            let backing_lot =
-                                ctx.accounts.bonding_curve.backing_per_lot_with_treasury(
-                                    lot,
-                                    tpool
-                                );
-
-                            // 3) Update EMA, then read it
-                            ctx.accounts.bonding_curve.update_ema_lot_price(
-                                market_lot,
-                                ema_alpha_bps
-                            );
-                            let ema_lot = ctx.accounts.bonding_curve.ema_lot_price;
-
-                            // 4) Build trigger thresholds
-                            let backing_thr = (((backing_lot as u128) *
-                                (backing_mult_bps as u128)) /
-                                10_000u128) as u64;
-                            let ema_thr = (((ema_lot as u128) * (ema_drop_bps as u128)) /
-                                10_000u128) as u64;
-                            let trigger_thr = backing_thr.max(ema_thr);
-
-                            msg!(
-                                "BB[chk]: lot={} mkt={} bkt={} ema={} thr={} tpool={}",
-                                lot,
-                                market_lot,
-                                backing_lot,
-                                ema_lot,
-                                trigger_thr,
-                                tpool
-                            );
-
-                            // 5) Trigger condition - balanced approach
-                            // Allow buybacks when price is below threshold AND
-                            // either backing is above a minimum OR the price drop is significant
-                            // NOTE: min_backing_lamports loaded from params above, dont override it here!
-                            let significant_drop = market_lot <= ema_lot / 2; // Price dropped by 50% or more from EMA
+        Ok(())
+    }
 
-                            if
-                                market_lot <= trigger_thr &&
-                                (backing_lot >= min_backing_lamports.into() || significant_drop)
-                            {
-                                // Budget from treasury (lamports)
-                                let budget = (((tpool as u128) * (spend_bps as u128)) /
-                                    10_000u128) as u64;
-                                msg!("BB[budget]: spend_bps={} budget={}", spend_bps, budget);
-
-                                // Convert budget → tokens via CPMM inversion
-                                let mut amount =
-                                    ctx.accounts.bonding_curve.tokens_for_budget(budget);
-                                msg!("BB[size0]: tokens_for_budget -> {}", amount);
-
-                                // Cap to ≤10% of on-curve tokens; ensure it never floors to 0 when rtok>0
-                                let mut max_supply_buy = if rtok == 0 {
-                                    0
-                                } else {
-                                    (rtok.saturating_mul(max_supply_bps) / 10_000).max(1)
-                                };
-                                // Never exceed available curve inventory
-                                if max_supply_buy > rtok {
-                                    max_supply_buy = rtok;
-                                }
-
-                                msg!(
-                                    "BB[caps]: rtok={} cap_bps={} cap_tokens={}",
-                                    rtok,
-                                    max_supply_bps,
-                                    max_supply_buy
-                                );
-
-                                if max_supply_buy > 0 {
-                                    amount = amount.min(max_supply_buy);
-                                    msg!("BB[size1]: after cap -> {}", amount);
-                                } else {
-                                    msg!("BB[warn]: cap_tokens=0 (rtok=0), cannot buy");
-                                }
-
-                                // Guard rounding to zero → try minimum 1 atomic unit if affordable
-                                if amount == 0 {
-                                    match ctx.accounts.bonding_curve.buy_quote_checked(1) {
-                                        Some(min_cost) => {
-                                            msg!(
-                                                "BB[fallback-atomic]: min_cost={} tpool={}",
-                                                min_cost,
-                                                tpool
-                                            );
-                                            if tpool >= min_cost && max_supply_buy >= 1 {
-                                                amount = 1;
-                                                msg!("BB[fallback-atomic]: amount set to 1");
-                                            } else {
-                                                msg!(
-                                                    "BB[fallback-atomic]: insufficient tpool or cap<1"
-                                                );
-                                            }
-                                        }
-                                        None => msg!("BB[fallback-atomic]: unsafe quote(1)"),
-                                    }
-                                }
-
-                                // Optional second fallback: try one *lot* if affordable and within caps
-                                if amount == 0 && lot > 0 && max_supply_buy >= lot {
-                                    match ctx.accounts.bonding_curve.buy_quote_checked(lot) {
-                                        Some(lot_cost) => {
-                                            msg!(
-                                                "BB[fallback-lot]: lot={} cost={} tpool={}",
-                                                lot,
-                                                lot_cost,
-                                                tpool
-                                            );
-                                            if tpool >= lot_cost {
-                                                amount = lot.min(max_supply_buy);
-                                                msg!("BB[fallback-lot]: amount set to {}", amount);
-                                            } else {
-                                                msg!(
-                                                    "BB[fallback-lot]: insufficient tpool for lot"
-                                                );
-                                            }
-                                        }
-                                        None => msg!("BB[fallback-lot]: unsafe lot quote"),
-                                    }
-                                }
-
-                                if amount > 0 {
-                                    // Final quote & funds check
-                                    match ctx.accounts.bonding_curve.buy_quote_checked(amount) {
-                                        None => {
-                                            msg!(
-                                                "BB[skip]: unsafe quote for amount={} (vT={})",
-                                                amount,
-                                                ctx.accounts.bonding_curve.virtual_token_reserves
-                                            );
-                                        }
-                                        Some(bb_cost) => {
-                                            msg!(
-                                                "BB[quote]: amount={} bb_cost={} tpool={}",
-                                                amount,
-                                                bb_cost,
-                                                tpool
-                                            );
-                                            require!(
-                                                tpool >= bb_cost,
-                                                HorseFunError::InsufficientTreasuryFunds
-                                            );
-
-                                            // Snapshot pre-state for delta logs
-                                            let vtr0 =
-                                                ctx.accounts.bonding_curve.virtual_token_reserves;
-                                            let rtr0 =
-                                                ctx.accounts.bonding_curve.real_token_reserves;
-                                            let vsr0 =
-                                                ctx.accounts.bonding_curve.virtual_sol_reserves;
-                                            let rsr0 = ctx.accounts.bonding_curve.real_sol_reserves;
-                                            let tpool0 =
-                                                ctx.accounts.bonding_curve.treasury_fee_pool;
-
-                                            // Spend from treasury pool (book-keeping)
-                                            ctx.accounts.bonding_curve.treasury_fee_pool =
-                                                ctx.accounts.bonding_curve.treasury_fee_pool.saturating_sub(
-                                                    bb_cost
-                                                );
-
-                                            // Apply the same state transition as a buy:
-                                            ctx.accounts.bonding_curve.virtual_token_reserves =
-                                                ctx.accounts.bonding_curve.virtual_token_reserves.saturating_sub(
-                                                    amount
-                                                );
-                                            ctx.accounts.bonding_curve.virtual_sol_reserves =
-                                                ctx.accounts.bonding_curve.virtual_sol_reserves.saturating_add(
-                                                    bb_cost
-                                                );
-
-                                            ctx.accounts.bonding_curve.real_token_reserves =
-                                                ctx.accounts.bonding_curve.real_token_reserves.saturating_sub(
-                                                    amount
-                                                );
-                                            ctx.accounts.bonding_curve.real_sol_reserves =
-                                                ctx.accounts.bonding_curve.real_sol_reserves.saturating_add(
-                                                    bb_cost
-                                                );
-
-                                            // Intended burn sizing
-                                            let mint_supply_before = ctx.accounts.mint.supply;
-
-                                            burn_amount = amount; // burn the full buyback size
-                                            let curve_ata_bal =
-                                                ctx.accounts.associated_bonding_curve.amount;
-
-                                            msg!("SHould burn:{}", burn_amount);
-                                            msg!(
-                                                "SBB[burn-plan]: burn_amount={} curve_ata_bal={} mint_supply_before={}",
-                                                burn_amount,
-                                                curve_ata_bal,
-                                                ctx.accounts.mint.supply
-                                            );
-
-                                            // Safety guard
-                                            let burn_ok =
-                                                burn_amount > 0 && curve_ata_bal >= burn_amount;
-
-                                            msg!(
-                                                "SBB[burn-guard]: curve_ata>=burn?={} ({}>=?)",
-                                                burn_ok,
-                                                curve_ata_bal
-                                            );
-
-                                            let burn_ok =
-                                                burn_amount > 0 && curve_ata_bal >= burn_amount;
-                                            msg!(
-                                                "SBB[burn-guard]: curve_ata>=intended?={} ({}>=?)",
-                                                burn_ok,
-                                                curve_ata_bal
-                                            );
-
-                                            // ---- BURN ENABLED ----
-                                            if burn_ok {
-                                                {
-                                                    // Isolated scope to avoid borrow conflicts during CPI
-                                                    helpers::burn_from_curve_ata_on_sell(
-                                                        &ctx,
-                                                        burn_amount
-                                                    )?;
-                                                }
-
-                                                // IMPORTANT: do NOT subtract real_token_reserves again here.
-                                                // We already reduced it by `amount` when applying the buyback math above.
-                                                // Only update the cached total supply:
-
-                                                // Also update circulating supply when tokens are burned
-                                                ctx.accounts.bonding_curve.circulating_supply =
-                                                    ctx.accounts.bonding_curve.circulating_supply.saturating_sub(
-                                                        burn_amount
-                                                    );
-                                            }
-
-                                            // track totals
-                                            ctx.accounts.bonding_curve.total_treasury_spent =
-                                                ctx.accounts.bonding_curve.total_treasury_spent.saturating_add(
-                                                    bb_cost
-                                                );
-
-                                            let mut burned: u64 = 0;
-                                            if burn_ok {
-                                                // burn already executed above
-                                                burned = burn_amount;
-                                                ctx.accounts.bonding_curve.total_burned_supply =
-                                                    ctx.accounts.bonding_curve.total_burned_supply.saturating_add(
-                                                        burn_amount
-                                                    );
-                                            }
-
-                                            let price_lpt = if amount > 0 {
-                                                bb_cost.saturating_div(amount)
-                                            } else {
-                                                0
-                                            };
-
-                                            // Update buyback variables for TradeEvent at end of function
-                                            is_buyback = true;
-                                            burn_amount = burned;
-                                            price_lamports_per_token = price_lpt;
-
-                                            // Note: TradeEvent will be emitted at end of function
-
-                                            // Delta log
-                                            msg!(
-                                                "BB[go]: amount={} cost={} new_tpool={}",
-                                                amount,
-                                                bb_cost,
-                                                ctx.accounts.bonding_curve.treasury_fee_pool
-                                            );
-                                            msg!(
-                                                "BB[delta]: Δvtok=-{} Δrtok=-{} Δvsol=+{} Δrsol=+{} Δtpool=-{}",
-                                                vtr0.saturating_sub(
-                                                    ctx.accounts.bonding_curve.virtual_token_reserves
-                                                ),
-                                                rtr0.saturating_sub(
-                                                    ctx.accounts.bonding_curve.real_token_reserves
-                                                ),
-                                                ctx.accounts.bonding_curve.virtual_sol_reserves.saturating_sub(
-                                                    vsr0
-                                                ),
-                                                ctx.accounts.bonding_curve.real_sol_reserves.saturating_sub(
-                                                    rsr0
-                                                ),
-                                                tpool0.saturating_sub(
-                                                    ctx.accounts.bonding_curve.treasury_fee_pool
-                                                )
-                                            );
-                                        }
-                                    }
-                                } else {
-                                    msg!("BB[skip]: amount=0 after sizing and fallbacks");
-                                }
-                            } else {
-                                msg!(
-                                    "BB[skip]: no trigger mkt={} thr={} bkt={}",
-                                    market_lot,
-                                    trigger_thr,
-                                    backing_lot
-                                );
-                            }
-                        }
-                    }
-                }
-            } // Close the else block for burn limit check
-        } else {
-            msg!("Buybacks are disabled globally");
-        }
-        msg!(
-            "SELL[payout-plan]: sol_output={} fee={} user_gets={}",
-            sol_output,
-            fee,
-            sol_output.saturating_sub(fee)
+    /// Sets the minimum number of seconds between `propose_authority` and `accept_authority`.
+    /// Locked while a transfer is pending, so that transfer keeps the delay it was proposed under.
+    pub fn set_authority_transfer_delay(ctx: Context<SetParams>, delay_secs: i64) -> Result<()> {
+        require!(ctx.accounts.global.initialized, HorseFunError::NotInitialized);
+        require_keys_eq!(
+            ctx.accounts.user.key(),
+            ctx.accounts.global.authority,
+            HorseFunError::NotAuthorized
+        );
+        require!(delay_secs >= 0, HorseFunError::InvalidAuthorityTransferDelay);
+        require!(
+            ctx.accounts.global.pending_authority == Pubkey::default(),
+            HorseFunError::AuthorityTransferPending
         );
 
-        // transfer the sol from the bonding curve to the user (excluding fee)
-        helpers::transfer_sol_from_bonding_curve_to_user(&ctx, sol_output.saturating_sub(fee))?;
+        ctx.accounts.global.authority_transfer_delay = delay_secs;
 
-        // handle fee distribution from the retained amount
-        helpers::transfer_sol_from_bonding_curve_to_fee_recipient(&mut ctx, fee)?;
+        let event = AuthorityTransferDelaySetEvent {
+            delay_secs,
+            timestamp: Clock::get()?.unix_timestamp,
+        };
 
-        // Calculate fee splits for this trade to emit in event
-        let (_, creator_fee_for_trade, _, _) = ctx.accounts.global.get_fee_splits(fee);
+        emit_cpi!(event.clone());
+        emit!(event);
 
-        // 🔧 Calculate if this user is a valid early bird
-        let user_pos = ctx.accounts.holder_stats.entry_position;
-        let is_early_bird =
-            user_pos > 0 &&
-            user_pos != u64::MAX &&
-            user_pos <= ctx.accounts.global.early_bird_cutoff;
+        Ok(())
+    }
 
-        // Emit final TradeEvent with all information (including possible buyback)
-        let trade_event = TradeEvent {
-            user: ctx.accounts.user.key(),
-            sol_amount: sol_output,
-            token_amount: amount,
-            is_buy: false,
-            mint: ctx.accounts.mint.key(),
-            timestamp: Clock::get()?.unix_timestamp,
-            virtual_sol_reserves: ctx.accounts.bonding_curve.virtual_sol_reserves,
-            virtual_token_reserves: ctx.accounts.bonding_curve.virtual_token_reserves,
-            circulating_supply: ctx.accounts.bonding_curve.circulating_supply,
-            real_token_reserves: ctx.accounts.bonding_curve.real_token_reserves,
-            real_sol_reserves: ctx.accounts.bonding_curve.real_sol_reserves,
-            creator_fee_pool: ctx.accounts.bonding_curve.creator_fee_pool,
-            treasury_fee_pool: ctx.accounts.bonding_curve.treasury_fee_pool,
-            total_fees_accrued: ctx.accounts.bonding_curve.total_fees_accrued,
-            total_treasury_fees_accrued: ctx.accounts.bonding_curve.total_treasury_fees_accrued,
-            creator_fee_amount: creator_fee_for_trade, // Fee earned by creator from THIS trade
-            fee_recipient: ctx.accounts.bonding_curve.creator_wallet, // Current creator (CTO-aware)
-            is_buyback,
-            burn_amount,
-            price_lamports_per_token,
-            total_burned_supply: ctx.accounts.bonding_curve.total_burned_supply,
-            total_treasury_spent: ctx.accounts.bonding_curve.total_treasury_spent,
-            early_bird_pool: ctx.accounts.bonding_curve.early_bird_pool,
-            total_early_bird_fees_accrued: ctx.accounts.bonding_curve.total_early_bird_fees_accrued,
-            user_position: ctx.accounts.holder_stats.entry_position,
-            user_balance: ctx.accounts.holder_stats.current_balance,
-            early_bird_cutoff: ctx.accounts.global.early_bird_cutoff,
-            total_buyers: ctx.accounts.bonding_curve.total_buyers,
-            early_bird_valid_count: ctx.accounts.bonding_curve.early_bird_valid_count,
-            is_early_bird,
+    /// Starts a Global authority transfer. The new key must call `accept_authority`
+    /// once the configured delay has passed; proposing again replaces the pending key.
+    pub fn propose_authority(ctx: Context<SetParams>, new_authority: Pubkey) -> Result<()> {
+        require!(ctx.accounts.global.initialized, HorseFunError::NotInitialized);
+        require_keys_eq!(
+            ctx.accounts.user.key(),
+            ctx.accounts.global.authority,
+            HorseFunError::NotAuthorized
+        );
+        require!(new_authority != Pubkey::default(), HorseFunError::InvalidAuthority);
+
+        let now = Clock::get()?.unix_timestamp;
+        let global = &mut ctx.accounts.global;
+        global.pending_authority = new_authority;
+        global.authority_proposed_at = now;
+
+        let event = AuthorityProposedEvent {
+            authority: global.authority,
+            pending_authority: new_authority,
+            accept_after: now.saturating_add(global.authority_transfer_delay),
+            timestamp: now,
         };
 
-        emit_cpi!(trade_event);
-        emit!(trade_event);
+        emit_cpi!(event.clone());
+        emit!(event);
 
         Ok(())
     }
 
-    pub fn withdraw(ctx: Context<Withdraw>) -> Result<()> {
-        require!(ctx.accounts.bonding_curve.complete, HorseFunError::BondingCurveNotComplete);
+    /// Completes a Global authority transfer; must be signed by the pending authority.
+    pub fn accept_authority(ctx: Context<SetParams>) -> Result<()> {
+        require!(ctx.accounts.global.initialized, HorseFunError::NotInitialized);
+        let global = &mut ctx.accounts.global;
+        require!(global.pending_authority != Pubkey::default(), HorseFunError::NoPendingAuthority);
         require_keys_eq!(
-            config_feature::withdraw_authority::ID,
             ctx.accounts.user.key(),
+            global.pending_authority,
             HorseFunError::NotAuthorized
         );
 
-        // Save creator fees - they can only be claimed by creator
-        let creator_fees = ctx.accounts.bonding_curve.creator_fee_pool;
+        let now = Clock::get()?.unix_timestamp;
+        require!(
+            now >= global.authority_proposed_at.saturating_add(global.authority_transfer_delay),
+            HorseFunError::AuthorityTransferTooEarly
+        );
 
-        // transfer the tokens from the bonding curve to the admin
-        helpers::transfer_tokens_from_bonding_curve_to_admin(
-            &ctx,
-            ctx.accounts.associated_bonding_curve.amount
-        )?;
+        let previous_authority = global.authority;
+        global.authority = global.pending_authority;
+        global.pending_authority = Pubkey::default();
+        global.authority_proposed_at = 0;
 
-        // transfer the sol from the bonding curve to the admin
-        // Exclude creator fees from withdrawal
-        helpers::transfer_sol_from_bonding_curve_to_admin(
-            &ctx,
-            ctx.accounts.bonding_curve.real_sol_reserves - creator_fees
-        )?;
+        let event = AuthorityTransferredEvent {
+            previous_authority,
+            new_authority: global.authority,
+            timestamp: now,
+        };
 
-        // update the bonding curve parameters
-        // Preserve creator fees
-        ctx.accounts.bonding_curve.real_sol_reserves = creator_fees;
-        ctx.accounts.bonding_curve.virtual_sol_reserves = 0;
-        ctx.accounts.bonding_curve.real_token_reserves = 0;
-        ctx.accounts.bonding_curve.virtual_token_reserves = 0;
+        emit_cpi!(event.clone());
+        emit!(event);
 
         Ok(())
     }
 
-    #[event]
-    pub struct CreatorFeeClaimedEvent {
-        pub mint: Pubkey,
-        pub claimer: Pubkey,
-        pub amount: u64,
-        pub total_fees_accrued: u64,
-        pub timestamp: i64,
-    }
+    /// Drops a pending Global authority transfer (current authority only).
+    pub fn cancel_authority_transfer(ctx: Context<SetParams>) -> Result<()> {
+        require!(ctx.accounts.global.initialized, HorseFunError::NotInitialized);
+        require_keys_eq!(
+            ctx.accounts.user.key(),
+            ctx.accounts.global.authority,
+            HorseFunError::NotAuthorized
+        );
+        let global = &mut ctx.accounts.global;
+        require!(global.pending_authority != Pubkey::default(), HorseFunError::NoPendingAuthority);
 
-    #[event]
-    pub struct StreamerIdentityCancelledEvent {
-        pub user: Pubkey,
-        pub streamer_id: String,
-        pub timestamp: i64,
-    }
+        let cancelled_authority = global.pending_authority;
+        global.pending_authority = Pubkey::default();
+        global.authority_proposed_at = 0;
 
-    /// Register a streamer identity (only callable by platform authority)
-    pub fn register_streamer_identity(
-        ctx: Context<RegisterStreamerIdentity>,
-        streamer_id: String
-    ) -> Result<()> {
-        msg!("Registering streamer identity:");
-        msg!(" - Platform Authority: {}", ctx.accounts.platform_authority.key());
-        msg!(" - User Wallet: {}", ctx.accounts.user.key());
-        msg!(" - Streamer ID: {}", streamer_id);
+        let event = AuthorityTransferCancelledEvent {
+            authority: global.authority,
+            cancelled_authority,
+            timestamp: Clock::get()?.unix_timestamp,
+        };
 
-        // Initialize StreamerIdRegistry
-        let registry = &mut ctx.accounts.streamer_id_registry;
-        registry.streamer_id = streamer_id.clone();
-        registry.wallet = ctx.accounts.user.key();
-        msg!(" - PDA Being Created: {}", ctx.accounts.streamer_identity.key());
+        emit_cpi!(event.clone());
+        emit!(event);
 
-        // Validate streamer_id format
-        require!(
-            !streamer_id.is_empty() && streamer_id.len() <= 50,
-            HorseFunError::InvalidStreamerId
-        );
+        Ok(())
+    }
 
-        msg!("Streamer ID validation passed");
+    /// Creates the Roles account, handing every role to the Global authority.
+    pub fn init_roles(ctx: Context<InitRoles>) -> Result<()> {
+        require!(ctx.accounts.global.initialized, HorseFunError::NotInitialized);
+        require_keys_eq!(
+            ctx.accounts.user.key(),
+            ctx.accounts.global.authority,
+            HorseFunError::NotAuthorized
+        );
 
-        // Initialize StreamerIdentity
-        let streamer_identity = &mut ctx.accounts.streamer_identity;
-        streamer_identity.wallet = ctx.accounts.user.key();
-        streamer_identity.streamer_id = streamer_id.clone();
-        streamer_identity.verified = true; // Set by platform
+        let authority = ctx.accounts.global.authority;
+        let roles = &mut ctx.accounts.roles;
+        roles.admin = authority;
+        roles.withdrawer = authority;
+        roles.identity_verifier = authority;
+        roles.cto_operator = authority;
+        roles.pauser = authority;
+        roles.fee_manager = authority;
+        roles.pending = [Pubkey::default(); ROLE_COUNT];
+        roles.version = Roles::VERSION;
 
-        msg!("StreamerIdentity account initialized:");
-        msg!(" - Wallet: {}", streamer_identity.wallet);
-        msg!(" - Streamer ID: {}", streamer_identity.streamer_id);
-        msg!(" - Verified: {}", streamer_identity.verified);
+        Ok(())
+    }
 
-        // Calculate and log PDA seeds
-        let (expected_pda, bump) = Pubkey::find_program_address(
-            &[b"streamer-identity", ctx.accounts.user.key().as_ref()],
-            ctx.program_id
+    /// Proposes a new key for a role (admin only). The key takes over once it calls
+    /// `accept_role`; proposing the default pubkey withdraws a pending proposal.
+    pub fn propose_role(ctx: Context<UpdateRoles>, role: Role, new_key: Pubkey) -> Result<()> {
+        require_keys_eq!(
+            ctx.accounts.user.key(),
+            ctx.accounts.roles.admin,
+            HorseFunError::NotAuthorized
         );
-        msg!("PDA Details:");
-        msg!(" - Expected Address: {}", expected_pda);
-        msg!(" - Bump Seed: {}", bump);
-        msg!(" - Actual Address: {}", ctx.accounts.streamer_identity.key());
 
-        // Emit registration event
-        emit!(StreamerIdentityRegisteredEvent {
-            user: ctx.accounts.user.key(),
-            streamer_id,
+        ctx.accounts.roles.pending[role as usize] = new_key;
+
+        let event = RoleProposedEvent {
+            role,
+            current: ctx.accounts.roles.key(role),
+            proposed: new_key,
             timestamp: Clock::get()?.unix_timestamp,
-        });
+        };
+
+        emit_cpi!(event.clone());
+        emit!(event);
 
-        msg!("Streamer identity registration complete!");
         Ok(())
     }
 
-    /// Claims creator's share of fees (can be claimed by creator or withdraw authority)
-    /// Cancel a streamer identity registration (only callable by platform authority)
-    pub fn cancel_streamer_identity(
-        ctx: Context<CancelStreamerIdentity>,
-        streamer_id: String
-    ) -> Result<()> {
-        msg!("Cancelling streamer identity:");
-        msg!(" - Platform Authority: {}", ctx.accounts.platform_authority.key());
-        msg!(" - User Wallet: {}", ctx.accounts.user.key());
-        msg!(" - Streamer ID: {}", streamer_id);
+    /// Completes a role transfer; must be signed by the proposed key.
+    pub fn accept_role(ctx: Context<UpdateRoles>, role: Role) -> Result<()> {
+        let roles = &mut ctx.accounts.roles;
+        let pending = roles.pending[role as usize];
+        require!(pending != Pubkey::default(), HorseFunError::NoPendingRole);
+        require_keys_eq!(ctx.accounts.user.key(), pending, HorseFunError::NotAuthorized);
+
+        let previous = roles.key(role);
+        *roles.key_mut(role) = pending;
+        roles.pending[role as usize] = Pubkey::default();
+
+        let event = RoleAcceptedEvent {
+            role,
+            previous,
+            new_key: pending,
+            timestamp: Clock::get()?.unix_timestamp,
+        };
 
-        // Verify the streamer_id matches both accounts
-        require!(
-            ctx.accounts.streamer_identity.streamer_id == streamer_id &&
-                ctx.accounts.streamer_id_registry.streamer_id == streamer_id,
-            HorseFunError::InvalidStreamerId
-        );
+        emit_cpi!(event.clone());
+        emit!(event);
 
-        // Verify the wallet matches in both accounts
-        require!(
-            ctx.accounts.streamer_identity.wallet == ctx.accounts.user.key() &&
-                ctx.accounts.streamer_id_registry.wallet == ctx.accounts.user.key(),
-            HorseFunError::UnauthorizedUser
-        );
+        Ok(())
+    }
 
-        msg!("Validation passed, closing accounts:");
-        msg!(" - StreamerIdentity PDA: {}", ctx.accounts.streamer_identity.key());
-        msg!(" - StreamerIdRegistry PDA: {}", ctx.accounts.streamer_id_registry.key());
+    /// Pauses or resumes the whole program. While paused every user-facing state-changing
+    /// instruction fails, claims included. Deliberately left open: admin and role
+    /// instructions (set_*, propose/accept/cancel, migrate_*) so the operator can repair
+    /// state, and the exits that only hand back a user's own deposit or undo a pending
+    /// action: cancel_trade_commitment, cancel_launch and cancel_streamer_identity.
+    pub fn set_paused(ctx: Context<SetPaused>, paused: bool) -> Result<()> {
+        require!(ctx.accounts.global.initialized, HorseFunError::NotInitialized);
+        require_keys_eq!(
+            ctx.accounts.user.key(),
+            ctx.accounts.roles.pauser,
+            HorseFunError::NotAuthorized
+        );
 
-        // The accounts will be automatically closed due to the close constraint
-        // This will:
-        // 1. Transfer the lamports to the platform authority
-        // 2. Zero out the account data
-        // 3. Mark the account as closed
+        ctx.accounts.global.paused = paused;
 
-        // Emit cancellation event
-        emit!(StreamerIdentityCancelledEvent {
-            user: ctx.accounts.user.key(),
-            streamer_id,
+        let event = ProgramPausedSetEvent {
+            paused,
+            pauser: ctx.accounts.user.key(),
             timestamp: Clock::get()?.unix_timestamp,
-        });
+        };
+
+        emit_cpi!(event.clone());
+        emit!(event);
 
-        msg!("Streamer identity cancellation complete!");
-        msg!(" - Rent returned to: {}", ctx.accounts.platform_authority.key());
         Ok(())
     }
 
-    /// Reassigns the fee recipient for a token, overriding creator wallet and/or streamer ID
-    /// Only callable by platform authority as a failsafe for community protection
-    pub fn reassign_fee_recipient(
-        ctx: Context<ReassignFeeRecipient>,
-        new_recipient: Pubkey,
-        new_streamer_id: Option<String>
-    ) -> Result<()> {
-        // Verify that the signer is the platform authority
+    /// Freezes or unfreezes trading on a single curve. Claims and donations stay open
+    /// so holders can always withdraw what they have earned.
+    pub fn set_curve_frozen(ctx: Context<SetCurveFrozen>, frozen: bool) -> Result<()> {
         require_keys_eq!(
-            config_feature::platform_authority::ID,
-            ctx.accounts.platform_authority.key(),
+            ctx.accounts.user.key(),
+            ctx.accounts.roles.pauser,
             HorseFunError::NotAuthorized
         );
 
-        msg!("Reassigning fee recipient:");
-        msg!(" - Platform Authority: {}", ctx.accounts.platform_authority.key());
-        msg!(" - Bonding Curve: {}", ctx.accounts.bonding_curve.key());
-        msg!(" - Mint: {}", ctx.accounts.mint.key());
-        msg!(" - Current Creator: {}", ctx.accounts.bonding_curve.creator_wallet);
-        msg!(" - Current Streamer ID: {:?}", ctx.accounts.bonding_curve.creator_streamer_id);
-        msg!(" - New Recipient: {}", new_recipient);
-        msg!(" - New Streamer ID: {:?}", new_streamer_id);
-
-        // Validate streamer_id format if provided
-        if let Some(ref id) = new_streamer_id {
-            require!(!id.is_empty() && id.len() <= 50, HorseFunError::InvalidStreamerId);
-        }
-
-        // Store original values for event
-        let old_creator = ctx.accounts.bonding_curve.creator_wallet;
-        let old_streamer_id = ctx.accounts.bonding_curve.creator_streamer_id.clone();
-
-        // Update the bonding curve with new recipient info
-        ctx.accounts.bonding_curve.creator_wallet = new_recipient;
-        ctx.accounts.bonding_curve.creator_streamer_id = new_streamer_id.clone();
+        ctx.accounts.bonding_curve.load_mut()?.frozen = frozen as u8;
 
-        // Emit the event
-        // Emit CTO event with two separate struct constructions
-        emit_cpi!(CtoEvent {
-            mint: ctx.accounts.mint.key(),
-            old_creator,
-            old_streamer_id: old_streamer_id.clone(),
-            new_creator: new_recipient,
-            new_streamer_id: new_streamer_id.clone(),
-            platform_authority: ctx.accounts.platform_authority.key(),
-            timestamp: Clock::get()?.unix_timestamp,
-        });
-        emit!(CtoEvent {
+        let event = CurveFrozenSetEvent {
             mint: ctx.accounts.mint.key(),
-            old_creator,
-            old_streamer_id,
-            new_creator: new_recipient,
-            new_streamer_id,
-            platform_authority: ctx.accounts.platform_authority.key(),
+            frozen,
+            pauser: ctx.accounts.user.key(),
             timestamp: Clock::get()?.unix_timestamp,
-        });
+        };
+
+        emit_cpi!(event.clone());
+        emit!(event);
 
         Ok(())
     }
 
-    pub fn claim_creator_fees(ctx: Context<ClaimCreatorFees>) -> Result<()> {
-        msg!("Attempting to claim creator fees:");
-        msg!(" - Claimer Wallet: {}", ctx.accounts.user.key());
-        msg!(" - Bonding Curve: {}", ctx.accounts.bonding_curve.key());
-        msg!(" - Mint: {}", ctx.accounts.mint.key());
-        msg!(" - Available Fees: {}", ctx.accounts.bonding_curve.creator_fee_pool);
-
-        let fees = ctx.accounts.bonding_curve.creator_fee_pool;
-        require!(fees > 0, HorseFunError::NoFeesToClaim);
-
-        // Log verification path
-        if ctx.accounts.user.key() == config_feature::withdraw_authority::ID {
-            msg!("Claiming as withdraw authority");
-        } else {
-            match &ctx.accounts.bonding_curve.creator_streamer_id {
-                Some(expected_id) => {
-                    msg!("Token has streamer ID verification:");
-                    msg!(" - Expected Streamer ID: {}", expected_id);
-
-                    match &ctx.accounts.streamer_identity {
-                        Some(identity_acc) => {
-                            msg!(" - Provided Identity Account: {}", identity_acc.key());
-                            // Log identity details after verification
-                            if
-                                let Ok(identity) = StreamerIdentity::try_deserialize(
-                                    &mut &identity_acc.try_borrow_data()?[8..]
-                                )
-                            {
-                                msg!(" - Identity Wallet: {}", identity.wallet);
-                                msg!(" - Identity Streamer ID: {}", identity.streamer_id);
-                                msg!(" - Identity Verified: {}", identity.verified);
-                            } else {
-                                msg!(" - Failed to deserialize identity account");
-                            }
-                        }
-                        None => msg!(" - No streamer identity account provided"),
-                    }
-                }
-                None => {
-                    msg!("Token uses wallet-only verification:");
-                    msg!(
-                        " - Expected Creator Wallet: {}",
-                        ctx.accounts.bonding_curve.creator_wallet
-                    );
-                }
-            }
-        }
+    /// Creates or updates a curve preset that `create` can launch from.
+    /// Once params are timelocked, a preset's fee is capped at the Global fee.
+    pub fn set_curve_preset(
+        ctx: Context<SetCurvePreset>,
+        preset_id: u16,
+        name: String,
+        enabled: bool,
+        initial_virtual_token_reserves: u64,
+        initial_virtual_sol_reserves: u64,
+        initial_real_token_reserves: u64,
+        token_total_supply: u64,
+        fee_basis_points: u64,
+        early_bird_cutoff: u64,
+        launch_protection: LaunchProtection
+    ) -> Result<()> {
+        require!(ctx.accounts.global.initialized, HorseFunError::NotInitialized);
+        require_keys_eq!(
+            ctx.accounts.user.key(),
+            ctx.accounts.global.authority,
+            HorseFunError::NotAuthorized
+        );
+        // Preset 0 is reserved for the Global defaults
+        require!(preset_id != 0, HorseFunError::InvalidCurvePreset);
+        // Under a timelock a preset can't charge more than the (timelocked) Global fee
+        require!(
+            ctx.accounts.global.params_timelock_secs == 0 ||
+                fee_basis_points <= ctx.accounts.global.fee_basis_points,
+            HorseFunError::ParamsTimelocked
+        );
 
-        // Validate caller is either withdraw authority or verified creator
-        ctx.accounts.validate()?;
-        msg!("Creator validation passed");
+        let preset = &mut ctx.accounts.curve_preset;
+        preset.preset_id = preset_id;
+        preset.version = CurvePreset::VERSION;
+        preset.name = name;
+        preset.enabled = enabled;
+        preset.initial_virtual_token_reserves = initial_virtual_token_reserves;
+        preset.initial_virtual_sol_reserves = initial_virtual_sol_reserves;
+        preset.initial_real_token_reserves = initial_real_token_reserves;
+        preset.token_total_supply = token_total_supply;
+        preset.fee_basis_points = fee_basis_points;
+        preset.early_bird_cutoff = early_bird_cutoff;
+        preset.launch_protection = launch_protection;
+        preset.validate()?;
+
+        let event = CurvePresetSetEvent {
+            preset_id,
+            name: preset.name.clone(),
+            enabled,
+            initial_virtual_token_reserves,
+            initial_virtual_sol_reserves,
+            initial_real_token_reserves,
+            token_total_supply,
+            fee_basis_points,
+            early_bird_cutoff,
+            launch_protection,
+        };
 
-        msg!("Initiating fee transfer:");
-        msg!(" - From Bonding Curve: {}", ctx.accounts.bonding_curve.key());
-        msg!(" - To Wallet: {}", ctx.accounts.user.key());
-        msg!(" - Amount: {} lamports", fees);
+        emit_cpi!(event.clone());
+        emit!(event);
 
-        // Transfer fees to claimer
-        // After `ctx.accounts.validate()?;`
-        let fees = ctx.accounts.bonding_curve.creator_fee_pool;
-        require!(fees > 0, HorseFunError::NoFeesToClaim);
-        let mint_key = ctx.accounts.mint.key();
-        let bump_bytes = [ctx.bumps.bonding_curve];
-        let seeds = helpers::curve_seeds(&mint_key, &bump_bytes);
+        Ok(())
+    }
 
-        helpers::pda_transfer_lamports(
-            &ctx.accounts.bonding_curve.to_account_info(),
+    /// Upgrades the Global account to the current layout: grows it to `Global::SIZE`
+    /// and default-initializes new fields. A no-op once `version` is current.
+    pub fn migrate_global(ctx: Context<MigrateGlobal>) -> Result<()> {
+        let info = ctx.accounts.global.to_account_info();
+        helpers::realloc_account(
+            &info,
             &ctx.accounts.user.to_account_info(),
-            fees
+            &ctx.accounts.system_program.to_account_info(),
+            Global::SIZE
         )?;
 
-        msg!("Fee transfer successful");
+        let mut global = Global::try_deserialize(&mut &info.try_borrow_data()?[..])?;
+        require!(global.initialized, HorseFunError::NotInitialized);
+        require_keys_eq!(ctx.accounts.user.key(), global.authority, HorseFunError::NotAuthorized);
 
-        // Emit claim event
-        emit!(CreatorFeeClaimedEvent {
-            mint: ctx.accounts.mint.key(),
-            claimer: ctx.accounts.user.key(),
-            amount: fees,
-            total_fees_accrued: ctx.accounts.bonding_curve.total_fees_accrued,
+        let from_version = global.version;
+        global.migrate();
+        global.try_serialize(&mut &mut info.try_borrow_mut_data()?[..])?;
+
+        let event = AccountMigratedEvent {
+            account: info.key(),
+            from_version,
+            to_version: global.version,
             timestamp: Clock::get()?.unix_timestamp,
-        });
+        };
 
-        // Reset fee pool
-        ctx.accounts.bonding_curve.creator_fee_pool = 0;
-        msg!("Fee pool reset to 0");
+        emit_cpi!(event.clone());
+        emit!(event);
 
         Ok(())
     }
 
-    /// Send a message with a donation to a creator
-    pub fn send_message(ctx: Context<SendMessage>, message: String, amount: u64) -> Result<()> {
-        // Validate message length
-        require!(message.len() <= 200, HorseFunError::MessageTooLong);
-        require!(amount > 0, HorseFunError::InsufficientDonationAmount);
+    /// Converts a borsh-layout bonding curve into the zero-copy layout in place;
+    /// permissionless, the caller pays the extra rent. Run `migrate_global` first.
+    /// Zero-copy curves from an older version are grown and re-stamped; a no-op once
+    /// `version` is current.
+    pub fn migrate_bonding_curve(ctx: Context<MigrateBondingCurve>) -> Result<()> {
+        let info = ctx.accounts.bonding_curve.to_account_info();
+        // No borsh layout outgrew LegacyBondingCurve::SIZE, so anything longer is zero-copy
+        let is_zero_copy = info.data_len() > LegacyBondingCurve::SIZE;
+        let legacy = if is_zero_copy {
+            None
+        } else {
+            let mut legacy = LegacyBondingCurve::read(&info)?;
+            let from_version = legacy.version;
+            legacy.migrate(&ctx.accounts.global);
+            Some((from_version, legacy))
+        };
 
-        // Transfer SOL from user to message list account
-        transfer(
-            CpiContext::new(ctx.accounts.system_program.to_account_info(), Transfer {
-                from: ctx.accounts.user.to_account_info(),
-                to: ctx.accounts.message_list.to_account_info(),
-            }),
-            amount
+        helpers::realloc_account(
+            &info,
+            &ctx.accounts.user.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            BondingCurve::SIZE
         )?;
 
-        // Create new message
-        let message_data = Message {
-            sender: ctx.accounts.user.key(),
-            amount,
-            message,
-            timestamp: Clock::get()?.unix_timestamp,
+        let from_version;
+        let mut bonding_curve = match legacy {
+            Some((legacy_version, legacy)) => {
+                from_version = legacy_version;
+                info.try_borrow_mut_data()?[8..].fill(0);
+                let mut bonding_curve = ctx.accounts.bonding_curve.load_mut()?;
+                legacy.write_to(&mut bonding_curve);
+                bonding_curve
+            }
+            None => {
+                let mut bonding_curve = ctx.accounts.bonding_curve.load_mut()?;
+                from_version = bonding_curve.version;
+                if from_version >= BondingCurve::VERSION {
+                    return Ok(());
+                }
+                bonding_curve.migrate(&ctx.accounts.global);
+                bonding_curve
+            }
         };
 
-        // Update message list
-        ctx.accounts.message_list.messages.push(message_data.clone());
-        ctx.accounts.message_list.total_received += amount;
-        ctx.accounts.message_list.unclaimed_amount += amount;
+        let event = AccountMigratedEvent {
+            account: info.key(),
+            from_version,
+            to_version: bonding_curve.version,
+            timestamp: Clock::get()?.unix_timestamp,
+        };
+        drop(bonding_curve);
 
-        // Emit event
-        emit!(MessageSentEvent {
-            mint: ctx.accounts.mint.key(),
-            sender: ctx.accounts.user.key(),
-            amount,
-            message: message_data.message,
-            timestamp: message_data.timestamp,
-        });
+        emit_cpi!(event.clone());
+        emit!(event);
 
         Ok(())
     }
 
-    /// Claim all unclaimed donations for a creator
-    pub fn claim_donations(ctx: Context<ClaimDonations>) -> Result<()> {
-        let unclaimed = ctx.accounts.message_list.unclaimed_amount;
-        require!(unclaimed > 0, HorseFunError::NoDonationsToClaim);
-
-        // Validate caller is either withdraw authority or verified creator
-        ctx.accounts.validate()?;
-
-        let amount = ctx.accounts.message_list.unclaimed_amount;
-        require!(amount > 0, HorseFunError::NoDonationsToClaim);
-
-        let mint_key = ctx.accounts.mint.key();
-        let bump_bytes = [ctx.bumps.message_list];
-        let seeds = helpers::msglist_seeds(&mint_key, &bump_bytes);
-        helpers::pda_transfer_lamports(
-            &ctx.accounts.message_list.to_account_info(),
+    /// Adds the version byte to a holder stats account created before it existed.
+    /// Permissionless, the caller pays the extra rent; a no-op once `version` is current.
+    pub fn migrate_holder_stats(ctx: Context<MigrateAccount>) -> Result<()> {
+        let (from_version, to_version) = helpers::migrate_borsh_account(
+            &ctx.accounts.account.to_account_info(),
             &ctx.accounts.user.to_account_info(),
-            amount
+            &ctx.accounts.system_program.to_account_info(),
+            HolderStats::SIZE,
+            HolderStats::migrate
         )?;
-        // Reset unclaimed amount while preserving total_received
-        ctx.accounts.message_list.unclaimed_amount = 0;
 
-        // Emit event with full claimed amount
-        emit!(DonationClaimedEvent {
-            mint: ctx.accounts.mint.key(),
-            creator: ctx.accounts.user.key(),
-            amount: unclaimed,
+        let event = AccountMigratedEvent {
+            account: ctx.accounts.account.key(),
+            from_version,
+            to_version,
             timestamp: Clock::get()?.unix_timestamp,
-            remaining_unclaimed: 0,
-        });
+        };
+
+        emit_cpi!(event.clone());
+        emit!(event);
 
         Ok(())
     }
 
-    /// Claim early bird rewards for eligible holders
-    pub fn claim_early_bird_rewards(ctx: Context<ClaimEarlyBirdRewards>) -> Result<()> {
-        let holder_stats = &mut ctx.accounts.holder_stats;
-        let bonding_curve = &mut ctx.accounts.bonding_curve;
-        let global = &ctx.accounts.global;
+    /// `migrate_holder_stats` for streamer identities.
+    pub fn migrate_streamer_identity(ctx: Context<MigrateAccount>) -> Result<()> {
+        let (from_version, to_version) = helpers::migrate_borsh_account(
+            &ctx.accounts.account.to_account_info(),
+            &ctx.accounts.user.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            StreamerIdentity::SIZE,
+            StreamerIdentity::migrate
+        )?;
 
-        // Check if early bird rewards are enabled
-        require!(global.early_bird_enabled, HorseFunError::EarlyBirdDisabled);
+        let event = AccountMigratedEvent {
+            account: ctx.accounts.account.key(),
+            from_version,
+            to_version,
+            timestamp: Clock::get()?.unix_timestamp,
+        };
 
-        // ⭐ NEW: Require bonding curve to be complete before claiming
-        require!(bonding_curve.complete, HorseFunError::CurveNotComplete);
+        emit_cpi!(event.clone());
+        emit!(event);
 
-        // Check if user is eligible (within first X buyers AND not revoked)
-        // entry_position == 0: never bought before
-        // entry_position == u64::MAX: permanently revoked (sold)
-        // entry_position > 0 && <= cutoff: eligible early bird
-        require!(
-            holder_stats.entry_position > 0 &&
-                holder_stats.entry_position != u64::MAX &&
-                holder_stats.entry_position <= global.early_bird_cutoff,
-            HorseFunError::NotEarlyBird
-        );
+        Ok(())
+    }
 
-        // ⭐ SECURITY: Prevent double claiming
-        // fees_claimed > 0 means they already claimed their Early Bird reward
-        require!(holder_stats.fees_claimed == 0, HorseFunError::AlreadyClaimedEarlyBird);
+    /// `migrate_holder_stats` for streamer id registry entries.
+    pub fn migrate_streamer_id_registry(ctx: Context<MigrateAccount>) -> Result<()> {
+        let (from_version, to_version) = helpers::migrate_borsh_account(
+            &ctx.accounts.account.to_account_info(),
+            &ctx.accounts.user.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            StreamerIdRegistry::SIZE,
+            StreamerIdRegistry::migrate
+        )?;
 
-        // Calculate claimable amount using cached equal share
-        // The share was calculated once when curve completed: pool / valid_count
-        // This ensures ALL early birds get EXACTLY the same amount
-        let share = bonding_curve.early_bird_share_per_seat;
-        require!(share > 0, HorseFunError::NoRewardsToClaim);
+        let event = AccountMigratedEvent {
+            account: ctx.accounts.account.key(),
+            from_version,
+            to_version,
+            timestamp: Clock::get()?.unix_timestamp,
+        };
 
-        msg!("🐦 Claiming Early Bird Rewards:");
-        msg!(" - User Position: #{}", holder_stats.entry_position);
-        msg!(" - Valid Early Bird Seats at Completion: {}", bonding_curve.early_bird_valid_count);
-        msg!(" - Equal Share Per Seat (cached): {} lamports", share);
-        msg!(" - Pool Before Claim: {} lamports", bonding_curve.early_bird_pool);
+        emit_cpi!(event.clone());
+        emit!(event);
 
-        // Transfer SOL from bonding curve to user using PDA transfer
-        helpers::pda_transfer_lamports(
-            &bonding_curve.to_account_info(),
+        Ok(())
+    }
+
+    /// `migrate_holder_stats` for donation message lists.
+    pub fn migrate_message_list(ctx: Context<MigrateAccount>) -> Result<()> {
+        let (from_version, to_version) = helpers::migrate_borsh_account(
+            &ctx.accounts.account.to_account_info(),
             &ctx.accounts.user.to_account_info(),
-            share
+            &ctx.accounts.system_program.to_account_info(),
+            MessageList::SIZE,
+            MessageList::migrate
         )?;
 
-        // Update tracking
-        bonding_curve.early_bird_pool -= share;
-        holder_stats.fees_claimed += share;
-
-        emit!(EarlyBirdClaimed {
-            user: ctx.accounts.user.key(),
-            mint: ctx.accounts.mint.key(),
-            amount: share,
-            position: holder_stats.entry_position,
+        let event = AccountMigratedEvent {
+            account: ctx.accounts.account.key(),
+            from_version,
+            to_version,
             timestamp: Clock::get()?.unix_timestamp,
-        });
+        };
 
-        msg!("✅ Early Bird Rewards claimed successfully!");
+        emit_cpi!(event.clone());
+        emit!(event);
 
         Ok(())
     }
-}
-
-mod helpers {
-    use super::*;
 
-    /// Revoke Early Bird status permanently when user sells
-    /// Uses u64::MAX as sentinel value to prevent re-qualification on future buys
-    /// Also decrements the valid early bird count
-    pub fn revoke_early_bird_status(
-        holder_stats: &mut HolderStats,
-        bonding_curve: &mut BondingCurve,
-        global: &Global
-    ) {
-        if holder_stats.entry_position > 0 && holder_stats.entry_position != u64::MAX {
-            // Check if this position was within the early bird cutoff
-            if holder_stats.entry_position <= global.early_bird_cutoff {
-                // Decrement valid count since this early bird seat is now revoked
-                bonding_curve.early_bird_valid_count =
-                    bonding_curve.early_bird_valid_count.saturating_sub(1);
+    /// Moves a live curve onto new fee, early bird and buyback parameters.
+    /// Curves otherwise keep the values snapshotted at create. Bounds match the Global
+    /// setters; the early bird cutoff is fixed once the curve has its first buyer.
+    /// Once params are timelocked this can only move a curve onto the current Global
+    /// values, which have already been through `propose_params` / `execute_params`.
+    pub fn migrate_curve_params(
+        ctx: Context<MigrateCurveParams>,
+        fee_basis_points: u64,
+        creator_fee_share: u64,
+        platform_fee_share: u64,
+        treasury_fee_share: u64,
+        early_bird_fee_share: u64,
+        early_bird_cutoff: u64,
+        buyback_params: BuybackParams
+    ) -> Result<()> {
+        require!(ctx.accounts.global.initialized, HorseFunError::NotInitialized);
+        require_keys_eq!(
+            ctx.accounts.user.key(),
+            ctx.accounts.global.authority,
+            HorseFunError::NotAuthorized
+        );
+        require!(fee_basis_points <= MAX_FEE_BASIS_POINTS, HorseFunError::InvalidFeeBasisPoints);
+        helpers::check_fee_shares(
+            creator_fee_share,
+            platform_fee_share,
+            treasury_fee_share,
+            early_bird_fee_share
+        )?;
+        buyback_params.validate()?;
+        require!(
+            early_bird_cutoff > 0 && early_bird_cutoff <= MAX_EARLY_BIRD_CUTOFF,
+            HorseFunError::InvalidEarlyBirdConfig
+        );
+        let global = &ctx.accounts.global;
+        if global.params_timelock_secs != 0 {
+            require!(
+                fee_basis_points == global.fee_basis_points &&
+                    creator_fee_share == global.creator_fee_share &&
+                    platform_fee_share == global.platform_fee_share &&
+                    treasury_fee_share == global.treasury_fee_share &&
+                    early_bird_fee_share == global.early_bird_fee_share &&
+                    early_bird_cutoff == global.early_bird_cutoff &&
+                    buyback_params == global.buyback_params,
+                HorseFunError::ParamsTimelocked
+            );
+        }
+
+        let mut bonding_curve = ctx.accounts.bonding_curve.load_mut()?;
+        // Seats already handed out were counted against the old cutoff
+        require!(
+            early_bird_cutoff == bonding_curve.early_bird_cutoff || bonding_curve.total_buyers == 0,
+            HorseFunError::EarlyBirdCutoffLocked
+        );
+        let event = CurveParamsMigratedEvent {
+            mint: ctx.accounts.mint.key(),
+            old_fee_basis_points: bonding_curve.fee_basis_points,
+            old_creator_fee_share: bonding_curve.creator_fee_share,
+            old_platform_fee_share: bonding_curve.platform_fee_share,
+            old_treasury_fee_share: bonding_curve.treasury_fee_share,
+            old_early_bird_fee_share: bonding_curve.early_bird_fee_share,
+            old_early_bird_cutoff: bonding_curve.early_bird_cutoff,
+            old_buyback_params: bonding_curve.buyback_params(),
+            fee_basis_points,
+            creator_fee_share,
+            platform_fee_share,
+            treasury_fee_share,
+            early_bird_fee_share,
+            early_bird_cutoff,
+            buyback_params,
+            timestamp: Clock::get()?.unix_timestamp,
+        };
+
+        bonding_curve.fee_basis_points = fee_basis_points;
+        bonding_curve.creator_fee_share = creator_fee_share;
+        bonding_curve.platform_fee_share = platform_fee_share;
+        bonding_curve.treasury_fee_share = treasury_fee_share;
+        bonding_curve.early_bird_fee_share = early_bird_fee_share;
+        bonding_curve.early_bird_cutoff = early_bird_cutoff;
+        bonding_curve.buyback_params = buyback_params.into();
+        drop(bonding_curve);
+
+        emit_cpi!(event.clone());
+        emit!(event);
+
+        Ok(())
+    }
+
+    /// Sets or disables a per-mint fee override for partner streamers.
+    /// The override must stay within the bounds configured in `Global`.
+    pub fn set_fee_override(
+        ctx: Context<SetFeeOverride>,
+        enabled: bool,
+        fee_basis_points: u64,
+        creator_fee_share: u64,
+        platform_fee_share: u64,
+        treasury_fee_share: u64,
+        early_bird_fee_share: u64
+    ) -> Result<()> {
+        require!(ctx.accounts.global.initialized, HorseFunError::NotInitialized);
+        require_keys_eq!(
+            ctx.accounts.roles.fee_manager,
+            ctx.accounts.platform_authority.key(),
+            HorseFunError::NotAuthorized
+        );
+        helpers::check_fee_shares(
+            creator_fee_share,
+            platform_fee_share,
+            treasury_fee_share,
+            early_bird_fee_share
+        )?;
+        require!(
+            fee_basis_points <= ctx.accounts.global.max_override_fee_basis_points &&
+                platform_fee_share >= ctx.accounts.global.min_override_platform_fee_share,
+            HorseFunError::FeeOverrideOutOfBounds
+        );
+
+        let fee_override = &mut ctx.accounts.fee_override;
+        fee_override.mint = ctx.accounts.mint.key();
+        fee_override.version = FeeOverride::VERSION;
+        fee_override.enabled = enabled;
+        fee_override.fee_basis_points = fee_basis_points;
+        fee_override.creator_fee_share = creator_fee_share;
+        fee_override.platform_fee_share = platform_fee_share;
+        fee_override.treasury_fee_share = treasury_fee_share;
+        fee_override.early_bird_fee_share = early_bird_fee_share;
+
+        // Trades on this curve must now supply the override account
+        ctx.accounts.bonding_curve.load_mut()?.has_fee_override = enabled as u8;
+
+        let event = FeeOverrideSetEvent {
+            mint: ctx.accounts.mint.key(),
+            enabled,
+            fee_basis_points,
+            creator_fee_share,
+            platform_fee_share,
+            treasury_fee_share,
+            early_bird_fee_share,
+            platform_authority: ctx.accounts.platform_authority.key(),
+            timestamp: Clock::get()?.unix_timestamp,
+        };
+
+        emit_cpi!(event.clone());
+        emit!(event);
+
+        Ok(())
+    }
+
+    /// Creates a new coin and bonding curve.
+    pub fn create(
+        mut ctx: Context<Create>,
+        name: String,
+        symbol: String,
+        uri: String,
+        streamer_id: Option<String>,
+        curve_kind: CurveKind,
+        preset_id: Option<u16>,
+        trading_starts_at: Option<i64>,
+        initial_buy_amount: Option<u64>,
+        initial_buy_max_sol_cost: u64,
+        presale: Option<PresaleConfig>,
+        opening_auction_ends_at: Option<i64>
+    ) -> Result<()> {
+        helpers::check_not_paused(&ctx.accounts.global)?;
+
+        verbose_msg!("Creating new token:");
+        verbose_msg!(" - Creator Wallet: {}", ctx.accounts.user.key());
+        verbose_msg!(" - Mint Address: {}", ctx.accounts.mint.key());
+
+        // Log streamer ID details
+        match &streamer_id {
+            Some(id) => {
+                verbose_msg!(" - Streamer ID provided: {}", id);
+                require!(
+                    !id.is_empty() && id.len() <= MAX_STREAMER_ID_LEN,
+                    HorseFunError::InvalidStreamerId
+                );
+                verbose_msg!(" - Streamer ID validation passed");
+            }
+            None => verbose_msg!(" - No Streamer ID provided, using wallet-only verification"),
+        }
+
+        // Resolve the launch shape: a curve preset if requested, otherwise Global defaults
+        let preset = match preset_id {
+            Some(id) => {
+                let preset = ctx.accounts.curve_preset
+                    .as_ref()
+                    .ok_or(HorseFunError::InvalidCurvePreset)?;
+                require!(preset.preset_id == id && preset.enabled, HorseFunError::InvalidCurvePreset);
+                verbose_msg!(" - Curve preset: #{} ({})", preset.preset_id, preset.name);
+                CurvePreset::clone(preset)
+            }
+            None => ctx.accounts.global.default_curve_preset(),
+        };
+
+        // initialize the bonding curve parameters
+        let mut bonding_curve = ctx.accounts.bonding_curve.load_init()?;
+        bonding_curve.virtual_token_reserves = preset.initial_virtual_token_reserves;
+        bonding_curve.virtual_sol_reserves = preset.initial_virtual_sol_reserves;
+        bonding_curve.real_token_reserves = preset.initial_real_token_reserves;
+        bonding_curve.real_sol_reserves = 0;
+        bonding_curve.token_total_supply = preset.token_total_supply;
+        bonding_curve.circulating_supply = preset.token_total_supply;
+        bonding_curve.complete = 0;
+        bonding_curve.total_burned_supply = 0;
+        bonding_curve.total_treasury_spent = 0;
+
+        // Curve shape is fixed for the life of the token
+        bonding_curve.curve_kind = curve_kind as u8;
+        bonding_curve.initial_virtual_token_reserves = preset.initial_virtual_token_reserves;
+        bonding_curve.initial_virtual_sol_reserves = preset.initial_virtual_sol_reserves;
+
+        // Snapshot the preset economics onto the curve
+        bonding_curve.preset_id = preset.preset_id;
+        bonding_curve.fee_basis_points = preset.fee_basis_points;
+        bonding_curve.early_bird_cutoff = preset.early_bird_cutoff;
+        bonding_curve.created_slot = Clock::get()?.slot;
+
+        // Scheduled launch: trading stays closed until `trading_starts_at`
+        let now = Clock::get()?.unix_timestamp;
+        if let Some(starts_at) = trading_starts_at {
+            require!(starts_at > now, HorseFunError::InvalidTradingStart);
+            verbose_msg!(" - Trading starts at: {}", starts_at);
+        }
+        bonding_curve.trading_starts_at = trading_starts_at.unwrap_or(now);
+        bonding_curve.launch_cancelled = 0;
+
+        // Optional allowlist phase, running from trading start until `presale.ends_at`
+        if let Some(presale) = presale {
+            require!(
+                presale.ends_at > bonding_curve.trading_starts_at,
+                HorseFunError::InvalidPresale
+            );
+            verbose_msg!(" - Presale ends at: {}", presale.ends_at);
+        }
+        bonding_curve.presale = presale.unwrap_or_default();
+
+        // Optional opening auction: buys stay closed until it is settled
+        bonding_curve.auction = OpeningAuction::default();
+        if let Some(ends_at) = opening_auction_ends_at {
+            require!(ends_at > now, HorseFunError::InvalidAuctionWindow);
+            // Bids are neither allowlisted nor capped per wallet, so an auction would
+            // hand out tokens around a presale or the launch caps
+            require!(
+                presale.is_none() && preset.launch_protection.window_slots == 0,
+                HorseFunError::AuctionLaunchConflict
+            );
+            verbose_msg!(" - Opening auction ends at: {}", ends_at);
+            bonding_curve.auction.ends_at = ends_at;
+        }
+        // Immediate launches open now; scheduled and auctioned ones on their first buy
+        if trading_starts_at.is_none() && opening_auction_ends_at.is_none() {
+            bonding_curve.trading_opened_slot = bonding_curve.created_slot;
+        }
+        bonding_curve.launch_protection = preset.launch_protection;
+        bonding_curve.creator_fee_share = ctx.accounts.global.creator_fee_share;
+        bonding_curve.platform_fee_share = ctx.accounts.global.platform_fee_share;
+        bonding_curve.treasury_fee_share = ctx.accounts.global.treasury_fee_share;
+        bonding_curve.early_bird_fee_share = ctx.accounts.global.early_bird_fee_share;
+        bonding_curve.buyback_params = ctx.accounts.global.buyback_params.into();
+
+        // Set creator info
+        bonding_curve.creator_wallet = ctx.accounts.user.key();
+        bonding_curve.version = BondingCurve::VERSION;
+        bonding_curve.set_creator_streamer_id(streamer_id.as_deref());
+        bonding_curve.creator_fee_pool = 0;
+        bonding_curve.treasury_fee_pool = 0;
+        bonding_curve.total_fees_accrued = 0;
+        bonding_curve.total_treasury_fees_accrued = 0;
+        bonding_curve.ema_lot_price = 0;
+
+        // Initialize early bird fields
+        bonding_curve.early_bird_pool = 0;
+        bonding_curve.total_buyers = 0;
+        bonding_curve.total_early_bird_fees_accrued = 0;
+        // Initialize with cutoff value - this will be decremented when early birds sell
+        bonding_curve.early_bird_valid_count = 0;
+        bonding_curve.early_bird_share_per_seat = 0; // Will be set when curve completes
+
+        // Release the curve before the CPIs below
+        let launch_times = (
+            bonding_curve.trading_starts_at,
+            bonding_curve.presale.ends_at,
+            bonding_curve.auction.ends_at,
+        );
+        drop(bonding_curve);
+
+        // set the metadata for the token
+        helpers::set_metadata(&ctx, name.clone(), symbol.clone(), uri.clone())?;
+
+        // mint tokens to the bonding curve
+        helpers::mint_to_bonding_curve(&ctx, preset.token_total_supply)?;
+
+        // revoke the mint authority
+        helpers::revoke_mint_authority(&ctx)?;
+
+        emit_cpi!(CreateEvent {
+            name: name.clone(),
+            symbol: symbol.clone(),
+            uri: uri.clone(),
+            mint: ctx.accounts.mint.key(),
+            bonding_curve: ctx.accounts.bonding_curve.key(),
+            user: ctx.accounts.user.key(),
+            curve_kind,
+            preset_id: preset.preset_id,
+            trading_starts_at: launch_times.0,
+            presale_ends_at: launch_times.1,
+            opening_auction_ends_at: launch_times.2,
+        });
+
+        emit!(CreateEvent {
+            name,
+            symbol,
+            uri,
+            mint: ctx.accounts.mint.key(),
+            bonding_curve: ctx.accounts.bonding_curve.key(),
+            user: ctx.accounts.user.key(),
+            curve_kind,
+            preset_id: preset.preset_id,
+            trading_starts_at: launch_times.0,
+            presale_ends_at: launch_times.1,
+            opening_auction_ends_at: launch_times.2,
+        });
+
+        // Optional creator dev-buy in the same instruction, so nobody can buy in between.
+        // Only for launches that open immediately, without a presale or auction.
+        if let Some(amount) = initial_buy_amount {
+            helpers::process_creator_buy(&mut ctx, amount, initial_buy_max_sol_cost)?;
+        }
+
+        Ok(())
+    }
+
+    /// Buys tokens from a bonding curve.
+    /// `deadline_unix_ts` / `max_slot` reject the order if it lands after the given time or slot.
+    /// `allowlist_proof` is only checked while the curve is in its presale phase.
+    pub fn buy(
+        mut ctx: Context<Buy>,
+        amount: u64,
+        max_sol_cost: u64,
+        deadline_unix_ts: Option<i64>,
+        max_slot: Option<u64>,
+        allowlist_proof: Vec<[u8; 32]>
+    ) -> Result<()> {
+        helpers::check_trade_deadline(deadline_unix_ts, max_slot)?;
+        let bonding_curve = ctx.accounts.bonding_curve.load()?;
+        let fee_override = helpers::active_fee_override(
+            &bonding_curve,
+            &ctx.accounts.fee_override
+        )?;
+        let fee_discount_bps = ctx.accounts.global.fee_discount_bps(
+            ctx.accounts.holder_stats.total_volume
+        );
+
+        // Cap amount to available reserves
+        let available_amount = std::cmp::min(
+            amount,
+            bonding_curve.real_token_reserves
+        );
+
+        // Calculate price for the capped amount
+        let sol_cost = bonding_curve.buy_quote(available_amount as u128);
+        let fee = bonding_curve.get_fee(sol_cost, fee_override.as_ref(), fee_discount_bps);
+
+        // Check slippage
+        require!(sol_cost.saturating_add(fee) <= max_sol_cost, HorseFunError::TooMuchSolRequired);
+
+        helpers::check_presale(
+            &bonding_curve,
+            &ctx.accounts.holder_stats,
+            &ctx.accounts.user.key(),
+            available_amount,
+            &allowlist_proof
+        )?;
+
+        drop(bonding_curve);
+
+        helpers::process_buy(&mut ctx, available_amount, sol_cost, fee, fee_discount_bps)
+    }
+
+    /// Buys tokens from a bonding curve by spending an exact SOL budget (fee included).
+    /// The token amount is sized on-chain and capped at the remaining curve inventory.
+    pub fn buy_exact_sol_in(
+        mut ctx: Context<Buy>,
+        sol_amount: u64,
+        min_tokens_out: u64,
+        deadline_unix_ts: Option<i64>,
+        max_slot: Option<u64>,
+        allowlist_proof: Vec<[u8; 32]>
+    ) -> Result<()> {
+        helpers::check_trade_deadline(deadline_unix_ts, max_slot)?;
+        require!(sol_amount > 0, HorseFunError::InvalidAmount);
+        let bonding_curve = ctx.accounts.bonding_curve.load()?;
+        let fee_override = helpers::active_fee_override(
+            &bonding_curve,
+            &ctx.accounts.fee_override
+        )?;
+        let fee_discount_bps = ctx.accounts.global.fee_discount_bps(
+            ctx.accounts.holder_stats.total_volume
+        );
+
+        // Split the budget into the part that moves the curve and the fee on top of it
+        let max_sol_cost = bonding_curve.get_cost_for_budget(
+            sol_amount,
+            fee_override.as_ref(),
+            fee_discount_bps
+        );
+
+        // Size the purchase with the curve shape's own inversion, then cap to available reserves
+        let mut available_amount = std::cmp::min(
+            bonding_curve.tokens_for_budget(max_sol_cost),
+            bonding_curve.real_token_reserves
+        );
+        let mut sol_cost = bonding_curve.buy_quote(available_amount as u128);
+        let mut fee = bonding_curve.get_fee(sol_cost, fee_override.as_ref(), fee_discount_bps);
+
+        // buy_quote rounds up, so the inverted size can overshoot the budget by one unit
+        if sol_cost + fee > sol_amount && available_amount > 0 {
+            available_amount -= 1;
+            sol_cost = bonding_curve.buy_quote(available_amount as u128);
+            fee = bonding_curve.get_fee(sol_cost, fee_override.as_ref(), fee_discount_bps);
+        }
+
+        verbose_msg!(
+            "Buy (exact SOL in): budget={} sol_cost={} fee={} tokens={}",
+            sol_amount,
+            sol_cost,
+            fee,
+            available_amount
+        );
+
+        // Check slippage
+        require!(available_amount > 0, HorseFunError::InvalidAmount);
+        require!(sol_cost + fee <= sol_amount, HorseFunError::TooMuchSolRequired);
+        require!(available_amount >= min_tokens_out, HorseFunError::TooFewTokensReceived);
+
+        helpers::check_presale(
+            &bonding_curve,
+            &ctx.accounts.holder_stats,
+            &ctx.accounts.user.key(),
+            available_amount,
+            &allowlist_proof
+        )?;
+
+        drop(bonding_curve);
+
+        helpers::process_buy(&mut ctx, available_amount, sol_cost, fee, fee_discount_bps)
+    }
+
+    /// Sells tokens into a bonding curve.
+    /// When `sell_all` is set, `amount` is ignored and the full token balance is sold.
+    pub fn sell(
+        mut ctx: Context<Sell>,
+        amount: u64,
+        min_sol_output: u64,
+        sell_all: bool,
+        deadline_unix_ts: Option<i64>,
+        max_slot: Option<u64>
+    ) -> Result<()> {
+        helpers::check_trade_deadline(deadline_unix_ts, max_slot)?;
+
+        let amount = if sell_all { ctx.accounts.associated_user.amount } else { amount };
+        require!(amount > 0, HorseFunError::InvalidAmount);
+        let bonding_curve = ctx.accounts.bonding_curve.load()?;
+        let fee_override = helpers::active_fee_override(
+            &bonding_curve,
+            &ctx.accounts.fee_override
+        )?;
+        let fee_discount_bps = ctx.accounts.global.fee_discount_bps(
+            ctx.accounts.holder_stats.total_volume
+        );
+
+        let sol_output = bonding_curve.sell_quote(amount as u128);
+        let fee = bonding_curve.get_fee(sol_output, fee_override.as_ref(), fee_discount_bps);
+
+        // check that the sol cost is within the slippage tolerance
+        require!(
+            sol_output.saturating_sub(fee) >= min_sol_output,
+            HorseFunError::TooLittleSolReceived
+        );
+
+        drop(bonding_curve);
+
+        helpers::process_sell(&mut ctx, amount, sol_output, fee, fee_discount_bps)
+    }
+
+    /// Sells just enough tokens to receive an exact amount of SOL (net of fees).
+    pub fn sell_exact_sol_out(
+        mut ctx: Context<Sell>,
+        sol_amount: u64,
+        max_tokens_in: u64,
+        deadline_unix_ts: Option<i64>,
+        max_slot: Option<u64>
+    ) -> Result<()> {
+        helpers::check_trade_deadline(deadline_unix_ts, max_slot)?;
+        require!(sol_amount > 0, HorseFunError::InvalidAmount);
+        let bonding_curve = ctx.accounts.bonding_curve.load()?;
+        let fee_override = helpers::active_fee_override(
+            &bonding_curve,
+            &ctx.accounts.fee_override
+        )?;
+        let fee_discount_bps = ctx.accounts.global.fee_discount_bps(
+            ctx.accounts.holder_stats.total_volume
+        );
+
+        // Gross curve output needed so that output - fee >= sol_amount
+        let target_output = bonding_curve
+            .get_output_for_net(sol_amount, fee_override.as_ref(), fee_discount_bps)
+            .ok_or(HorseFunError::ArithmeticOverflow)?;
+
+        // Invert sell_quote to size the tokens; None when the curve cannot pay that much
+        let amount = bonding_curve
+            .tokens_for_sell_output(target_output)
+            .ok_or(HorseFunError::TooLittleSolReceived)?;
+
+        let sol_output = bonding_curve.sell_quote(amount as u128);
+        let fee = bonding_curve.get_fee(sol_output, fee_override.as_ref(), fee_discount_bps);
+
+        verbose_msg!(
+            "Sell (exact SOL out): target={} sol_output={} fee={} tokens={}",
+            sol_amount,
+            sol_output,
+            fee,
+            amount
+        );
+
+        // Check slippage
+        require!(amount <= max_tokens_in, HorseFunError::TooManyTokensRequired);
+        require!(
+            sol_output.saturating_sub(fee) >= sol_amount,
+            HorseFunError::TooLittleSolReceived
+        );
+
+        drop(bonding_curve);
+
+        helpers::process_sell(&mut ctx, amount, sol_output, fee, fee_discount_bps)
+    }
+
+    /// Commits to a hidden trade: `keccak(side || amount || limit || salt)`.
+    /// Optionally escrows SOL on the commitment, refunded when it is revealed or cancelled.
+    /// Rejected while paused or frozen; reveals go through `buy` / `sell` and their checks.
+    pub fn commit_trade(
+        ctx: Context<CommitTrade>,
+        commitment: [u8; 32],
+        escrow_lamports: u64
+    ) -> Result<()> {
+        helpers::check_not_paused(&ctx.accounts.global)?;
+        require!(ctx.accounts.bonding_curve.load()?.frozen == 0, HorseFunError::CurveFrozen);
+
+        let trade_commitment = &mut ctx.accounts.trade_commitment;
+        trade_commitment.user = ctx.accounts.user.key();
+        trade_commitment.mint = ctx.accounts.mint.key();
+        trade_commitment.version = TradeCommitment::VERSION;
+        trade_commitment.commitment = commitment;
+        trade_commitment.committed_slot = Clock::get()?.slot;
+        trade_commitment.escrowed_lamports = escrow_lamports;
+
+        if escrow_lamports > 0 {
+            transfer(
+                CpiContext::new(ctx.accounts.system_program.to_account_info(), Transfer {
+                    from: ctx.accounts.user.to_account_info(),
+                    to: ctx.accounts.trade_commitment.to_account_info(),
+                }),
+                escrow_lamports
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Reveals a committed buy and executes it at the current price.
+    pub fn reveal_buy(
+        ctx: Context<Buy>,
+        amount: u64,
+        max_sol_cost: u64,
+        salt: [u8; 32],
+        allowlist_proof: Vec<[u8; 32]>
+    ) -> Result<()> {
+        helpers::consume_trade_commitment(
+            &ctx.accounts.trade_commitment,
+            &ctx.accounts.user,
+            &ctx.accounts.global,
+            true,
+            amount,
+            max_sol_cost,
+            &salt
+        )?;
+        buy(ctx, amount, max_sol_cost, None, None, allowlist_proof)
+    }
+
+    /// Reveals a committed sell and executes it at the current price.
+    pub fn reveal_sell(
+        ctx: Context<Sell>,
+        amount: u64,
+        min_sol_output: u64,
+        salt: [u8; 32]
+    ) -> Result<()> {
+        helpers::consume_trade_commitment(
+            &ctx.accounts.trade_commitment,
+            &ctx.accounts.user,
+            &ctx.accounts.global,
+            false,
+            amount,
+            min_sol_output,
+            &salt
+        )?;
+        sell(ctx, amount, min_sol_output, false, None, None)
+    }
+
+    /// Drops a commitment without trading; rent and escrowed SOL go back to the user.
+    pub fn cancel_trade_commitment(_ctx: Context<CancelTradeCommitment>) -> Result<()> {
+        Ok(())
+    }
+
+    /// Read-only quote for a buy or sell of `amount` tokens.
+    /// Simulate this instruction and decode the borsh `TradeQuote` from the return data;
+    /// it runs the same pricing, fee split and buyback sizing as `buy` / `sell`, and fails
+    /// with the same error when the trade would be rejected (pause, freeze, schedule,
+    /// auction, launch caps, price band). Presale proofs are not checked here; see
+    /// `TradeQuote::allowlist_required`.
+    pub fn quote_trade(ctx: Context<QuoteTrade>, is_buy: bool, amount: u64) -> Result<()> {
+        require!(amount > 0, HorseFunError::InvalidAmount);
+
+        // Work on a copy so the quote never touches account state
+        let mut curve = *ctx.accounts.bonding_curve.load()?;
+        require!(curve.complete == 0, HorseFunError::BondingCurveComplete);
+
+        let global = &ctx.accounts.global;
+        helpers::check_not_paused(global)?;
+        helpers::check_trading_open(&curve)?;
+
+        let fee_override = helpers::active_fee_override(&curve, &ctx.accounts.fee_override)?;
+        let referral_fee_share = helpers::referral_fee_share(global, &ctx.accounts.referral_vault);
+        let fee_discount_bps = ctx.accounts.holder_stats
+            .as_ref()
+            .map_or(0, |stats| global.fee_discount_bps(stats.total_volume));
+        let holder_balance = ctx.accounts.holder_stats
+            .as_ref()
+            .map_or(0, |stats| stats.current_balance);
+        let reference_price = curve.slot_reference_price(Clock::get()?.slot);
+
+        let (token_amount, sol_amount, fee) = if is_buy {
+            let token_amount = std::cmp::min(amount, curve.real_token_reserves);
+            let sol_cost = curve
+                .buy_quote_checked(token_amount)
+                .ok_or(HorseFunError::CurveCannotFill)?;
+            let fee = curve.get_fee(sol_cost, fee_override.as_ref(), fee_discount_bps);
+            helpers::check_launch_protection(&curve, holder_balance, token_amount, sol_cost)?;
+
+            curve.virtual_token_reserves -= token_amount;
+            curve.virtual_sol_reserves = curve.virtual_sol_reserves
+                .checked_add(sol_cost)
+                .ok_or(HorseFunError::ArithmeticOverflow)?;
+            curve.real_token_reserves -= token_amount;
+            curve.real_sol_reserves = curve.real_sol_reserves
+                .checked_add(sol_cost)
+                .ok_or(HorseFunError::ArithmeticOverflow)?;
+            helpers::check_price_band(&curve, global, ctx.accounts.mint.key(), reference_price)?;
+            if curve.real_token_reserves == 0 {
+                curve.complete = 1;
+            }
+
+            // Buy fees reach the pools before the buyback runs
+            let (_, creator_fee, treasury_fee, early_bird_fee, _) = curve.get_fee_splits(
+                fee,
+                fee_override.as_ref(),
+                referral_fee_share
+            );
+            curve.accrue_fees(creator_fee, treasury_fee, early_bird_fee);
+
+            (token_amount, sol_cost, fee)
+        } else {
+            let sol_output = curve.sell_quote(amount as u128);
+            let fee = curve.get_fee(sol_output, fee_override.as_ref(), fee_discount_bps);
+
+            curve.virtual_token_reserves = curve.virtual_token_reserves.saturating_add(amount);
+            curve.real_token_reserves = curve.real_token_reserves.saturating_add(amount);
+            curve.virtual_sol_reserves = curve.virtual_sol_reserves.saturating_sub(sol_output);
+            curve.real_sol_reserves = curve.real_sol_reserves.saturating_sub(
+                sol_output.saturating_sub(fee)
+            );
+            helpers::check_price_band(&curve, global, ctx.accounts.mint.key(), reference_price)?;
+
+            (amount, sol_output, fee)
+        };
+
+        let mut buyback = None;
+        if global.buybacks_enabled {
+            let params = curve.buyback_params();
+            if let Some(plan) = curve.plan_buyback(&params)? {
+                curve.apply_buyback(&plan);
+                curve.circulating_supply = curve.circulating_supply.saturating_sub(plan.amount);
+                curve.total_burned_supply = curve.total_burned_supply.saturating_add(plan.amount);
+                buyback = Some(plan);
+            }
+        }
+
+        let (platform_fee, creator_fee, treasury_fee, early_bird_fee, referral_fee) =
+            curve.get_fee_splits(fee, fee_override.as_ref(), referral_fee_share);
+        if !is_buy {
+            // Sell fees are distributed after the buyback
+            curve.accrue_fees(creator_fee, treasury_fee, early_bird_fee);
+        }
+
+        let presale = curve.presale;
+        let allowlist_required =
+            is_buy && presale.ends_at != 0 && Clock::get()?.unix_timestamp < presale.ends_at;
+
+        let quote = TradeQuote {
+            is_buy,
+            token_amount,
+            sol_amount,
+            fee,
+            net_sol_amount: if is_buy {
+                sol_amount.saturating_add(fee)
+            } else {
+                sol_amount.saturating_sub(fee)
+            },
+            platform_fee,
+            creator_fee,
+            treasury_fee,
+            early_bird_fee,
+            referral_fee,
+            buyback_triggered: buyback.is_some(),
+            buyback_cost: buyback.map_or(0, |plan| plan.cost),
+            burn_amount: buyback.map_or(0, |plan| plan.amount),
+            virtual_sol_reserves: curve.virtual_sol_reserves,
+            virtual_token_reserves: curve.virtual_token_reserves,
+            real_sol_reserves: curve.real_sol_reserves,
+            real_token_reserves: curve.real_token_reserves,
+            circulating_supply: curve.circulating_supply,
+            treasury_fee_pool: curve.treasury_fee_pool,
+            complete: curve.complete != 0,
+            allowlist_required,
+        };
+
+        let mut data = Vec::with_capacity(TradeQuote::SIZE);
+        quote.serialize(&mut data).map_err(|e| ProgramError::BorshIoError(e.to_string()))?;
+        anchor_lang::solana_program::program::set_return_data(&data);
+
+        Ok(())
+    }
+
+    pub fn withdraw(ctx: Context<Withdraw>) -> Result<()> {
+        let bonding_curve = ctx.accounts.bonding_curve.load()?;
+        require!(bonding_curve.complete != 0, HorseFunError::BondingCurveNotComplete);
+        require_keys_eq!(
+            ctx.accounts.roles.withdrawer,
+            ctx.accounts.user.key(),
+            HorseFunError::NotAuthorized
+        );
+
+        // Save creator fees - they can only be claimed by creator
+        let creator_fees = bonding_curve.creator_fee_pool;
+        let withdrawable = bonding_curve.real_sol_reserves
+            .checked_sub(creator_fees)
+            .ok_or(HorseFunError::ArithmeticOverflow)?;
+        drop(bonding_curve);
+
+        // transfer the tokens from the bonding curve to the admin
+        helpers::transfer_tokens_from_bonding_curve_to_admin(
+            &ctx,
+            ctx.accounts.associated_bonding_curve.amount
+        )?;
+
+        // transfer the sol from the bonding curve to the admin
+        // Exclude creator fees from withdrawal
+        helpers::transfer_sol_from_bonding_curve_to_admin(&ctx, withdrawable)?;
+
+        // update the bonding curve parameters
+        // Preserve creator fees
+        let mut bonding_curve = ctx.accounts.bonding_curve.load_mut()?;
+        bonding_curve.real_sol_reserves = creator_fees;
+        bonding_curve.virtual_sol_reserves = 0;
+        bonding_curve.real_token_reserves = 0;
+        bonding_curve.virtual_token_reserves = 0;
+
+        Ok(())
+    }
+
+    #[event]
+    pub struct CreatorFeeClaimedEvent {
+        pub mint: Pubkey,
+        pub claimer: Pubkey,
+        pub amount: u64,
+        pub total_fees_accrued: u64,
+        pub timestamp: i64,
+    }
+
+    #[event]
+    pub struct StreamerIdentityCancelledEvent {
+        pub user: Pubkey,
+        pub streamer_id: String,
+        pub timestamp: i64,
+    }
+
+    /// Register a streamer identity (only callable by the identity verifier role)
+    pub fn register_streamer_identity(
+        ctx: Context<RegisterStreamerIdentity>,
+        streamer_id: String
+    ) -> Result<()> {
+        helpers::check_not_paused(&ctx.accounts.global)?;
+
+        verbose_msg!("Registering streamer identity:");
+        verbose_msg!(" - Platform Authority: {}", ctx.accounts.platform_authority.key());
+        verbose_msg!(" - User Wallet: {}", ctx.accounts.user.key());
+        verbose_msg!(" - Streamer ID: {}", streamer_id);
+
+        // Initialize StreamerIdRegistry
+        let registry = &mut ctx.accounts.streamer_id_registry;
+        registry.streamer_id = streamer_id.clone();
+        registry.wallet = ctx.accounts.user.key();
+        registry.version = StreamerIdRegistry::VERSION;
+        verbose_msg!(" - PDA Being Created: {}", ctx.accounts.streamer_identity.key());
+
+        // Validate streamer_id format
+        require!(
+            !streamer_id.is_empty() && streamer_id.len() <= 50,
+            HorseFunError::InvalidStreamerId
+        );
+
+        verbose_msg!("Streamer ID validation passed");
+
+        // Initialize StreamerIdentity
+        let streamer_identity = &mut ctx.accounts.streamer_identity;
+        streamer_identity.wallet = ctx.accounts.user.key();
+        streamer_identity.streamer_id = streamer_id.clone();
+        streamer_identity.verified = true; // Set by platform
+        streamer_identity.version = StreamerIdentity::VERSION;
+
+        verbose_msg!("StreamerIdentity account initialized:");
+        verbose_msg!(" - Wallet: {}", streamer_identity.wallet);
+        verbose_msg!(" - Streamer ID: {}", streamer_identity.streamer_id);
+        verbose_msg!(" - Verified: {}", streamer_identity.verified);
+
+        // Calculate and log PDA seeds
+        let (expected_pda, bump) = Pubkey::find_program_address(
+            &[b"streamer-identity", ctx.accounts.user.key().as_ref()],
+            ctx.program_id
+        );
+        verbose_msg!("PDA Details:");
+        verbose_msg!(" - Expected Address: {}", expected_pda);
+        verbose_msg!(" - Bump Seed: {}", bump);
+        verbose_msg!(" - Actual Address: {}", ctx.accounts.streamer_identity.key());
+
+        // Emit registration event
+        emit!(StreamerIdentityRegisteredEvent {
+            user: ctx.accounts.user.key(),
+            streamer_id,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        verbose_msg!("Streamer identity registration complete!");
+        Ok(())
+    }
+
+    /// Claims creator's share of fees (can be claimed by creator or withdraw authority)
+    /// Cancel a streamer identity registration (only callable by the identity verifier role)
+    pub fn cancel_streamer_identity(
+        ctx: Context<CancelStreamerIdentity>,
+        streamer_id: String
+    ) -> Result<()> {
+        verbose_msg!("Cancelling streamer identity:");
+        verbose_msg!(" - Platform Authority: {}", ctx.accounts.platform_authority.key());
+        verbose_msg!(" - User Wallet: {}", ctx.accounts.user.key());
+        verbose_msg!(" - Streamer ID: {}", streamer_id);
+
+        // Verify the streamer_id matches both accounts
+        require!(
+            ctx.accounts.streamer_identity.streamer_id == streamer_id &&
+                ctx.accounts.streamer_id_registry.streamer_id == streamer_id,
+            HorseFunError::InvalidStreamerId
+        );
+
+        // Verify the wallet matches in both accounts
+        require!(
+            ctx.accounts.streamer_identity.wallet == ctx.accounts.user.key() &&
+                ctx.accounts.streamer_id_registry.wallet == ctx.accounts.user.key(),
+            HorseFunError::UnauthorizedUser
+        );
+
+        verbose_msg!("Validation passed, closing accounts:");
+        verbose_msg!(" - StreamerIdentity PDA: {}", ctx.accounts.streamer_identity.key());
+        verbose_msg!(" - StreamerIdRegistry PDA: {}", ctx.accounts.streamer_id_registry.key());
+
+        // The accounts will be automatically closed due to the close constraint
+        // This will:
+        // 1. Transfer the lamports to the platform authority
+        // 2. Zero out the account data
+        // 3. Mark the account as closed
+
+        // Emit cancellation event
+        emit!(StreamerIdentityCancelledEvent {
+            user: ctx.accounts.user.key(),
+            streamer_id,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        verbose_msg!("Streamer identity cancellation complete!");
+        verbose_msg!(" - Rent returned to: {}", ctx.accounts.platform_authority.key());
+        Ok(())
+    }
+
+    /// Reassigns the fee recipient for a token, overriding creator wallet and/or streamer ID
+    /// Only callable by the CTO operator role as a failsafe for community protection
+    pub fn reassign_fee_recipient(
+        ctx: Context<ReassignFeeRecipient>,
+        new_recipient: Pubkey,
+        new_streamer_id: Option<String>
+    ) -> Result<()> {
+        // Verify that the signer is the CTO operator
+        require_keys_eq!(
+            ctx.accounts.roles.cto_operator,
+            ctx.accounts.platform_authority.key(),
+            HorseFunError::NotAuthorized
+        );
+
+        let mut bonding_curve = ctx.accounts.bonding_curve.load_mut()?;
+        verbose_msg!("Reassigning fee recipient:");
+        verbose_msg!(" - Platform Authority: {}", ctx.accounts.platform_authority.key());
+        verbose_msg!(" - Bonding Curve: {}", ctx.accounts.bonding_curve.key());
+        verbose_msg!(" - Mint: {}", ctx.accounts.mint.key());
+        verbose_msg!(" - Current Creator: {}", bonding_curve.creator_wallet);
+        verbose_msg!(" - Current Streamer ID: {:?}", bonding_curve.creator_streamer_id());
+        verbose_msg!(" - New Recipient: {}", new_recipient);
+        verbose_msg!(" - New Streamer ID: {:?}", new_streamer_id);
+
+        // Validate streamer_id format if provided
+        if let Some(ref id) = new_streamer_id {
+            require!(
+                !id.is_empty() && id.len() <= MAX_STREAMER_ID_LEN,
+                HorseFunError::InvalidStreamerId
+            );
+        }
+
+        // Store original values for event
+        let old_creator = bonding_curve.creator_wallet;
+        let old_streamer_id = bonding_curve.creator_streamer_id().map(String::from);
+
+        // Update the bonding curve with new recipient info
+        bonding_curve.creator_wallet = new_recipient;
+        bonding_curve.set_creator_streamer_id(new_streamer_id.as_deref());
+        drop(bonding_curve);
+
+        // Emit the event
+        // Emit CTO event with two separate struct constructions
+        emit_cpi!(CtoEvent {
+            mint: ctx.accounts.mint.key(),
+            old_creator,
+            old_streamer_id: old_streamer_id.clone(),
+            new_creator: new_recipient,
+            new_streamer_id: new_streamer_id.clone(),
+            platform_authority: ctx.accounts.platform_authority.key(),
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        emit!(CtoEvent {
+            mint: ctx.accounts.mint.key(),
+            old_creator,
+            old_streamer_id,
+            new_creator: new_recipient,
+            new_streamer_id,
+            platform_authority: ctx.accounts.platform_authority.key(),
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    pub fn claim_creator_fees(ctx: Context<ClaimCreatorFees>) -> Result<()> {
+        helpers::check_not_paused(&ctx.accounts.global)?;
+
+        let bonding_curve = ctx.accounts.bonding_curve.load()?;
+        verbose_msg!("Attempting to claim creator fees:");
+        verbose_msg!(" - Claimer Wallet: {}", ctx.accounts.user.key());
+        verbose_msg!(" - Bonding Curve: {}", ctx.accounts.bonding_curve.key());
+        verbose_msg!(" - Mint: {}", ctx.accounts.mint.key());
+        verbose_msg!(" - Available Fees: {}", bonding_curve.creator_fee_pool);
+
+        let fees = bonding_curve.creator_fee_pool;
+        require!(fees > 0, HorseFunError::NoFeesToClaim);
+
+        // Log verification path
+        if ctx.accounts.user.key() == ctx.accounts.roles.withdrawer {
+            verbose_msg!("Claiming as withdraw authority");
+        } else {
+            match bonding_curve.creator_streamer_id() {
+                Some(expected_id) => {
+                    verbose_msg!("Token has streamer ID verification:");
+                    verbose_msg!(" - Expected Streamer ID: {}", expected_id);
+
+                    match &ctx.accounts.streamer_identity {
+                        Some(identity_acc) => {
+                            verbose_msg!(" - Provided Identity Account: {}", identity_acc.key());
+                            // Log identity details after verification
+                            if
+                                let Ok(identity) = StreamerIdentity::try_deserialize(
+                                    &mut &identity_acc.try_borrow_data()?[8..]
+                                )
+                            {
+                                verbose_msg!(" - Identity Wallet: {}", identity.wallet);
+                                verbose_msg!(" - Identity Streamer ID: {}", identity.streamer_id);
+                                verbose_msg!(" - Identity Verified: {}", identity.verified);
+                            } else {
+                                verbose_msg!(" - Failed to deserialize identity account");
+                            }
+                        }
+                        None => verbose_msg!(" - No streamer identity account provided"),
+                    }
+                }
+                None => {
+                    verbose_msg!("Token uses wallet-only verification:");
+                    verbose_msg!(" - Expected Creator Wallet: {}", bonding_curve.creator_wallet);
+                }
+            }
+        }
+
+        drop(bonding_curve);
+
+        // Validate caller is either withdraw authority or verified creator
+        ctx.accounts.validate()?;
+        verbose_msg!("Creator validation passed");
+
+        verbose_msg!("Initiating fee transfer:");
+        verbose_msg!(" - From Bonding Curve: {}", ctx.accounts.bonding_curve.key());
+        verbose_msg!(" - To Wallet: {}", ctx.accounts.user.key());
+        verbose_msg!(" - Amount: {} lamports", fees);
+
+        // Transfer fees to claimer
+        // After `ctx.accounts.validate()?;`
+        let fees = ctx.accounts.bonding_curve.load()?.creator_fee_pool;
+        require!(fees > 0, HorseFunError::NoFeesToClaim);
+        let mint_key = ctx.accounts.mint.key();
+        let bump_bytes = [ctx.bumps.bonding_curve];
+        let seeds = helpers::curve_seeds(&mint_key, &bump_bytes);
+
+        helpers::pda_transfer_lamports(
+            &ctx.accounts.bonding_curve.to_account_info(),
+            &ctx.accounts.user.to_account_info(),
+            fees
+        )?;
+
+        verbose_msg!("Fee transfer successful");
+
+        // Emit claim event
+        emit!(CreatorFeeClaimedEvent {
+            mint: ctx.accounts.mint.key(),
+            claimer: ctx.accounts.user.key(),
+            amount: fees,
+            total_fees_accrued: ctx.accounts.bonding_curve.load()?.total_fees_accrued,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        // Reset fee pool
+        ctx.accounts.bonding_curve.load_mut()?.creator_fee_pool = 0;
+        verbose_msg!("Fee pool reset to 0");
+
+        Ok(())
+    }
+
+    /// Send a message with a donation to a creator
+    pub fn send_message(ctx: Context<SendMessage>, message: String, amount: u64) -> Result<()> {
+        helpers::check_not_paused(&ctx.accounts.global)?;
+
+        // Validate message length
+        require!(message.len() <= 200, HorseFunError::MessageTooLong);
+        require!(amount > 0, HorseFunError::InsufficientDonationAmount);
+
+        // Transfer SOL from user to message list account
+        transfer(
+            CpiContext::new(ctx.accounts.system_program.to_account_info(), Transfer {
+                from: ctx.accounts.user.to_account_info(),
+                to: ctx.accounts.message_list.to_account_info(),
+            }),
+            amount
+        )?;
+
+        // Create new message
+        let message_data = Message {
+            sender: ctx.accounts.user.key(),
+            amount,
+            message,
+            timestamp: Clock::get()?.unix_timestamp,
+        };
+
+        // Update message list
+        ctx.accounts.message_list.version = MessageList::VERSION;
+        ctx.accounts.message_list.messages.push(message_data.clone());
+        ctx.accounts.message_list.total_received += amount;
+        ctx.accounts.message_list.unclaimed_amount += amount;
+
+        // Emit event
+        emit!(MessageSentEvent {
+            mint: ctx.accounts.mint.key(),
+            sender: ctx.accounts.user.key(),
+            amount,
+            message: message_data.message,
+            timestamp: message_data.timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Claim all unclaimed donations for a creator
+    pub fn claim_donations(ctx: Context<ClaimDonations>) -> Result<()> {
+        helpers::check_not_paused(&ctx.accounts.global)?;
+
+        let unclaimed = ctx.accounts.message_list.unclaimed_amount;
+        require!(unclaimed > 0, HorseFunError::NoDonationsToClaim);
+
+        // Validate caller is either withdraw authority or verified creator
+        ctx.accounts.validate()?;
+
+        let amount = ctx.accounts.message_list.unclaimed_amount;
+        require!(amount > 0, HorseFunError::NoDonationsToClaim);
+
+        let mint_key = ctx.accounts.mint.key();
+        let bump_bytes = [ctx.bumps.message_list];
+        let seeds = helpers::msglist_seeds(&mint_key, &bump_bytes);
+        helpers::pda_transfer_lamports(
+            &ctx.accounts.message_list.to_account_info(),
+            &ctx.accounts.user.to_account_info(),
+            amount
+        )?;
+        // Reset unclaimed amount while preserving total_received
+        ctx.accounts.message_list.unclaimed_amount = 0;
+
+        // Emit event with full claimed amount
+        emit!(DonationClaimedEvent {
+            mint: ctx.accounts.mint.key(),
+            creator: ctx.accounts.user.key(),
+            amount: unclaimed,
+            timestamp: Clock::get()?.unix_timestamp,
+            remaining_unclaimed: 0,
+        });
+
+        Ok(())
+    }
+
+    /// Claim early bird rewards for eligible holders
+    pub fn claim_early_bird_rewards(ctx: Context<ClaimEarlyBirdRewards>) -> Result<()> {
+        helpers::check_not_paused(&ctx.accounts.global)?;
+
+        let holder_stats = &mut ctx.accounts.holder_stats;
+        let mut bonding_curve = ctx.accounts.bonding_curve.load_mut()?;
+        let global = &ctx.accounts.global;
+
+        // Check if early bird rewards are enabled
+        require!(global.early_bird_enabled, HorseFunError::EarlyBirdDisabled);
+
+        // ⭐ NEW: Require bonding curve to be complete before claiming
+        require!(bonding_curve.complete != 0, HorseFunError::CurveNotComplete);
+
+        // Check if user is eligible (within first X buyers AND not revoked)
+        // entry_position == 0: never bought before
+        // entry_position == u64::MAX: permanently revoked (sold)
+        // entry_position > 0 && <= cutoff: eligible early bird
+        require!(
+            holder_stats.entry_position > 0 &&
+                holder_stats.entry_position != u64::MAX &&
+                holder_stats.entry_position <= bonding_curve.early_bird_cutoff,
+            HorseFunError::NotEarlyBird
+        );
+
+        // ⭐ SECURITY: Prevent double claiming
+        // fees_claimed > 0 means they already claimed their Early Bird reward
+        require!(holder_stats.fees_claimed == 0, HorseFunError::AlreadyClaimedEarlyBird);
+
+        // Calculate claimable amount using cached equal share
+        // The share was calculated once when curve completed: pool / valid_count
+        // This ensures ALL early birds get EXACTLY the same amount
+        let share = bonding_curve.early_bird_share_per_seat;
+        require!(share > 0, HorseFunError::NoRewardsToClaim);
+
+        verbose_msg!("🐦 Claiming Early Bird Rewards:");
+        verbose_msg!(" - User Position: #{}", holder_stats.entry_position);
+        verbose_msg!(
+            " - Valid Early Bird Seats at Completion: {}",
+            bonding_curve.early_bird_valid_count
+        );
+        verbose_msg!(" - Equal Share Per Seat (cached): {} lamports", share);
+        verbose_msg!(" - Pool Before Claim: {} lamports", bonding_curve.early_bird_pool);
+
+        // Transfer SOL from bonding curve to user using PDA transfer
+        helpers::pda_transfer_lamports(
+            &ctx.accounts.bonding_curve.to_account_info(),
+            &ctx.accounts.user.to_account_info(),
+            share
+        )?;
+
+        // Update tracking
+        bonding_curve.early_bird_pool = bonding_curve.early_bird_pool
+            .checked_sub(share)
+            .ok_or(HorseFunError::ArithmeticOverflow)?;
+        holder_stats.fees_claimed += share;
+        drop(bonding_curve);
+
+        emit!(EarlyBirdClaimed {
+            user: ctx.accounts.user.key(),
+            mint: ctx.accounts.mint.key(),
+            amount: share,
+            position: holder_stats.entry_position,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        verbose_msg!("✅ Early Bird Rewards claimed successfully!");
+
+        Ok(())
+    }
+
+    /// Creates the referral vault a referrer passes on buys and sells
+    pub fn init_referral_vault(ctx: Context<InitReferralVault>) -> Result<()> {
+        helpers::check_not_paused(&ctx.accounts.global)?;
+        ctx.accounts.referral_vault.referrer = ctx.accounts.referrer.key();
+        ctx.accounts.referral_vault.pending = 0;
+        ctx.accounts.referral_vault.total_earned = 0;
+        ctx.accounts.referral_vault.version = ReferralVault::VERSION;
+        Ok(())
+    }
+
+    /// Claims accumulated referral fees to the referrer wallet
+    pub fn claim_referral_fees(ctx: Context<ClaimReferralFees>) -> Result<()> {
+        helpers::check_not_paused(&ctx.accounts.global)?;
+
+        let amount = ctx.accounts.referral_vault.pending;
+        require!(amount > 0, HorseFunError::NoFeesToClaim);
+
+        helpers::pda_transfer_lamports(
+            &ctx.accounts.referral_vault.to_account_info(),
+            &ctx.accounts.referrer.to_account_info(),
+            amount
+        )?;
+        ctx.accounts.referral_vault.pending = 0;
+
+        let event = ReferralFeesClaimedEvent {
+            referrer: ctx.accounts.referrer.key(),
+            amount,
+            total_earned: ctx.accounts.referral_vault.total_earned,
+            timestamp: Clock::get()?.unix_timestamp,
+        };
+
+        emit_cpi!(event.clone());
+        emit!(event);
+
+        Ok(())
+    }
+
+    /// Moves a scheduled launch earlier. Only the creator, and only before trading opens.
+    pub fn set_trading_start(ctx: Context<UpdateLaunch>, trading_starts_at: i64) -> Result<()> {
+        helpers::check_not_paused(&ctx.accounts.global)?;
+        let now = Clock::get()?.unix_timestamp;
+        ctx.accounts.validate(now)?;
+
+        let mut bonding_curve = ctx.accounts.bonding_curve.load_mut()?;
+        require!(
+            trading_starts_at < bonding_curve.trading_starts_at,
+            HorseFunError::InvalidTradingStart
+        );
+
+        let old_trading_starts_at = bonding_curve.trading_starts_at;
+        // A start time in the past opens trading right away
+        bonding_curve.trading_starts_at = std::cmp::max(trading_starts_at, now);
+
+        let event = TradingStartUpdatedEvent {
+            mint: ctx.accounts.mint.key(),
+            old_trading_starts_at,
+            trading_starts_at: bonding_curve.trading_starts_at,
+            timestamp: now,
+        };
+        drop(bonding_curve);
+
+        emit_cpi!(event.clone());
+        emit!(event);
+
+        Ok(())
+    }
+
+    /// Cancels a scheduled launch. Only the creator, and only before trading opens.
+    /// Stays available while paused so a creator can always call off a launch.
+    pub fn cancel_launch(ctx: Context<UpdateLaunch>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        ctx.accounts.validate(now)?;
+
+        ctx.accounts.bonding_curve.load_mut()?.launch_cancelled = 1;
+
+        let event = LaunchCancelledEvent {
+            mint: ctx.accounts.mint.key(),
+            creator: ctx.accounts.user.key(),
+            timestamp: now,
+        };
+
+        emit_cpi!(event.clone());
+        emit!(event);
+
+        Ok(())
+    }
+
+    /// Commits SOL (fee included) to a curve's opening auction.
+    /// Can be called repeatedly to top up; funds sit in the auction escrow until claimed.
+    pub fn commit_opening_auction(ctx: Context<CommitOpeningAuction>, amount: u64) -> Result<()> {
+        helpers::check_not_paused(&ctx.accounts.global)?;
+        let mut bonding_curve = ctx.accounts.bonding_curve.load_mut()?;
+        require!(bonding_curve.frozen == 0, HorseFunError::CurveFrozen);
+        require!(amount > 0, HorseFunError::InvalidAmount);
+        require!(bonding_curve.launch_cancelled == 0, HorseFunError::LaunchCancelled);
+        let auction = bonding_curve.auction;
+        require!(
+            auction.ends_at != 0 && Clock::get()?.unix_timestamp < auction.ends_at,
+            HorseFunError::AuctionNotActive
+        );
+
+        transfer(
+            CpiContext::new(ctx.accounts.system_program.to_account_info(), Transfer {
+                from: ctx.accounts.user.to_account_info(),
+                to: ctx.accounts.auction_escrow.to_account_info(),
+            }),
+            amount
+        )?;
+
+        ctx.accounts.auction_escrow.version = AuctionEscrow::VERSION;
+
+        let bid = &mut ctx.accounts.auction_bid;
+        if bid.user == Pubkey::default() {
+            bid.user = ctx.accounts.user.key();
+            bid.mint = ctx.accounts.mint.key();
+            bid.bid_index = auction.bidder_count;
+            bid.version = AuctionBid::VERSION;
+            bonding_curve.auction.bidder_count += 1;
+        }
+        bid.amount = bid.amount.checked_add(amount).ok_or(HorseFunError::ArithmeticOverflow)?;
+        bonding_curve.auction.total_committed = auction.total_committed
+            .checked_add(amount)
+            .ok_or(HorseFunError::ArithmeticOverflow)?;
+
+        let event = AuctionCommitEvent {
+            mint: ctx.accounts.mint.key(),
+            user: ctx.accounts.user.key(),
+            amount,
+            user_total: bid.amount,
+            total_committed: bonding_curve.auction.total_committed,
+            timestamp: Clock::get()?.unix_timestamp,
+        };
+        drop(bonding_curve);
+
+        emit_cpi!(event.clone());
+        emit!(event);
+
+        Ok(())
+    }
+
+    /// Clears the opening auction at one uniform price once its window has ended.
+    /// Permissionless: anyone can crank it. Early bird seats are handed out by a
+    /// slot-hash seeded shuffle of the bidders, not by transaction order.
+    /// Takes two cranks: the first picks a future seed slot, so nobody knows the seed
+    /// when they choose to settle, and a later one settles with that slot's hash.
+    pub fn settle_opening_auction(ctx: Context<SettleOpeningAuction>) -> Result<()> {
+        helpers::check_not_paused(&ctx.accounts.global)?;
+        let mut curve = ctx.accounts.bonding_curve.load_mut()?;
+        require!(curve.frozen == 0, HorseFunError::CurveFrozen);
+        require!(curve.launch_cancelled == 0, HorseFunError::LaunchCancelled);
+        let mut auction = curve.auction;
+        require!(auction.ends_at != 0 && auction.settled == 0, HorseFunError::AuctionNotActive);
+        require!(
+            Clock::get()?.unix_timestamp >= auction.ends_at,
+            HorseFunError::AuctionNotEnded
+        );
+        require_keys_eq!(
+            ctx.accounts.global.fee_recipient,
+            ctx.accounts.fee_recipient.key(),
+            HorseFunError::NotAuthorized
+        );
+
+        let seed = match auction.seed_slot {
+            0 => None,
+            seed_slot => helpers::slot_hash_at(&ctx.accounts.slot_hashes, seed_slot)?,
+        };
+        // No seed slot yet, or it aged out of SlotHashes before anyone settled: pick one
+        let Some(seed) = seed else {
+            let seed_slot = Clock::get()?.slot + AUCTION_SEED_DELAY_SLOTS;
+            curve.auction.seed_slot = seed_slot;
+            drop(curve);
+
+            let event = AuctionSeedSlotSetEvent {
+                mint: ctx.accounts.mint.key(),
+                seed_slot,
+                timestamp: Clock::get()?.unix_timestamp,
+            };
+
+            emit_cpi!(event.clone());
+            emit!(event);
+
+            return Ok(());
+        };
+
+        // Everyone pays the same average price: the whole pot is one buy on the curve math
+        let budget = curve.get_cost_for_budget(auction.total_committed, None, 0);
+        let tokens = std::cmp::min(
+            curve.tokens_for_budget(budget),
+            curve.real_token_reserves.saturating_sub(1)
+        );
+        let sol_cost = curve.buy_quote(tokens as u128);
+        let fee = curve.get_fee(sol_cost, None, 0);
+        require!(sol_cost + fee <= auction.total_committed, HorseFunError::TooMuchSolRequired);
+
+        curve.virtual_token_reserves -= tokens;
+        curve.virtual_sol_reserves += sol_cost;
+        curve.real_token_reserves -= tokens;
+        curve.real_sol_reserves += sol_cost;
+
+        let (platform_fee, creator_fee, treasury_fee, early_bird_fee, _) = curve.get_fee_splits(
+            fee,
+            None,
+            0
+        );
+        curve.accrue_fees(creator_fee, treasury_fee, early_bird_fee);
+
+        let to_curve = sol_cost + creator_fee + treasury_fee + early_bird_fee;
+        if to_curve > 0 {
+            helpers::pda_transfer_lamports(
+                &ctx.accounts.auction_escrow.to_account_info(),
+                &ctx.accounts.bonding_curve.to_account_info(),
+                to_curve
+            )?;
+        }
+        if platform_fee > 0 {
+            helpers::pda_transfer_lamports(
+                &ctx.accounts.auction_escrow.to_account_info(),
+                &ctx.accounts.fee_recipient.to_account_info(),
+                platform_fee
+            )?;
+        }
+
+        // Reserve the remaining early bird positions for the auction and seed the shuffle
+        auction.seat_base = curve.total_buyers;
+        auction.seats = std::cmp::min(
+            curve.early_bird_cutoff.saturating_sub(curve.total_buyers),
+            auction.bidder_count
+        );
+        curve.total_buyers += auction.seats;
+        // Seats are valid from settlement on, so a curve that completes before every bidder
+        // has claimed still splits the early bird pool over all of them. Seats that turn
+        // out unused are released again in claim_opening_auction.
+        if ctx.accounts.global.early_bird_enabled {
+            curve.early_bird_valid_count += auction.seats;
+            auction.seats_counted = 1;
+        }
+        let (shuffle_a, shuffle_b) = helpers::auction_shuffle_params(&seed, auction.bidder_count);
+        auction.shuffle_a = shuffle_a;
+        auction.shuffle_b = shuffle_b;
+
+        auction.settled = 1;
+        auction.tokens_cleared = tokens;
+        auction.sol_cost = sol_cost;
+        auction.fee = fee;
+        curve.auction = auction;
+
+        let event = AuctionSettledEvent {
+            mint: ctx.accounts.mint.key(),
+            total_committed: auction.total_committed,
+            bidder_count: auction.bidder_count,
+            tokens_cleared: tokens,
+            sol_cost,
+            fee,
+            seats: auction.seats,
+            virtual_sol_reserves: curve.virtual_sol_reserves,
+            virtual_token_reserves: curve.virtual_token_reserves,
+            timestamp: Clock::get()?.unix_timestamp,
+        };
+        drop(curve);
+
+        emit_cpi!(event.clone());
+        emit!(event);
+
+        Ok(())
+    }
+
+    /// Pays out a bidder's pro-rata share of the cleared tokens plus any unspent SOL,
+    /// and assigns their shuffled early bird position. Refunds in full if the launch was cancelled.
+    pub fn claim_opening_auction(ctx: Context<ClaimOpeningAuction>) -> Result<()> {
+        helpers::check_not_paused(&ctx.accounts.global)?;
+
+        let bonding_curve = ctx.accounts.bonding_curve.load()?;
+        let auction = bonding_curve.auction;
+        let launch_cancelled = bonding_curve.launch_cancelled != 0;
+        drop(bonding_curve);
+        let bid_amount = ctx.accounts.auction_bid.amount;
+
+        if launch_cancelled {
+            helpers::pda_transfer_lamports(
+                &ctx.accounts.auction_escrow.to_account_info(),
+                &ctx.accounts.user.to_account_info(),
+                bid_amount
+            )?;
+            return Ok(());
+        }
+        require!(auction.settled != 0, HorseFunError::AuctionNotEnded);
+
+        let total = auction.total_committed as u128;
+        let mut tokens = (((auction.tokens_cleared as u128) * (bid_amount as u128)) / total) as u64;
+        let cost_share = (((auction.sol_cost as u128) * (bid_amount as u128)) / total) as u64;
+        let unspent = auction.total_committed - auction.sol_cost - auction.fee;
+        let refund = (((unspent as u128) * (bid_amount as u128)) / total) as u64;
+
+        // Pro-rata shares are floored; the last bid claimed takes the leftover tokens
+        let mut bonding_curve = ctx.accounts.bonding_curve.load_mut()?;
+        let progress = &mut bonding_curve.auction;
+        progress.claimed_count += 1;
+        if progress.claimed_count == auction.bidder_count {
+            tokens = auction.tokens_cleared.saturating_sub(progress.tokens_claimed);
+        }
+        progress.tokens_claimed = progress.tokens_claimed.saturating_add(tokens);
+        drop(bonding_curve);
+
+        if tokens > 0 {
+            let mint_key = ctx.accounts.mint.key();
+            let authority_seed = &[
+                b"bonding-curve".as_ref(),
+                mint_key.as_ref(),
+                &[ctx.bumps.bonding_curve],
+            ];
+            let seeds = [authority_seed.as_slice()];
+            token::transfer(
+                CpiContext::new_with_signer(
+                    ctx.accounts.token_program.to_account_info(),
+                    token::Transfer {
+                        from: ctx.accounts.associated_bonding_curve.to_account_info(),
+                        to: ctx.accounts.associated_user.to_account_info(),
+                        authority: ctx.accounts.bonding_curve.to_account_info(),
+                    },
+                    &seeds
+                ),
+                tokens
+            )?;
+        }
+        if refund > 0 {
+            helpers::pda_transfer_lamports(
+                &ctx.accounts.auction_escrow.to_account_info(),
+                &ctx.accounts.user.to_account_info(),
+                refund
+            )?;
+        }
+
+        let holder_stats = &mut ctx.accounts.holder_stats;
+        let mut bonding_curve = ctx.accounts.bonding_curve.load_mut()?;
+        if holder_stats.user == Pubkey::default() {
+            holder_stats.user = ctx.accounts.user.key();
+            holder_stats.mint = ctx.accounts.mint.key();
+            holder_stats.version = HolderStats::VERSION;
+        }
+        holder_stats.current_balance = holder_stats.current_balance
+            .checked_add(tokens)
+            .ok_or(HorseFunError::ArithmeticOverflow)?;
+        holder_stats.total_volume += cost_share;
+
+        // Shuffled rank decides who gets the reserved seats
+        let rank = helpers::auction_rank(&auction, ctx.accounts.auction_bid.bid_index);
+        if rank < auction.seats {
+            let takes_seat =
+                holder_stats.entry_position == 0 &&
+                cost_share >= ctx.accounts.global.early_bird_min_buy_sol;
+            if takes_seat {
+                holder_stats.entry_position = auction.seat_base + rank + 1;
+                // Legacy settlements did not count their seats up front
+                if
+                    auction.seats_counted == 0 &&
+                    ctx.accounts.global.early_bird_enabled &&
+                    holder_stats.entry_position <= bonding_curve.early_bird_cutoff
+                {
+                    bonding_curve.early_bird_valid_count += 1;
+                }
+            } else if auction.seats_counted != 0 {
+                // The bidder already holds a position or bid too little: release the seat
+                bonding_curve.early_bird_valid_count =
+                    bonding_curve.early_bird_valid_count.saturating_sub(1);
+            }
+        }
+        drop(bonding_curve);
+
+        let event = AuctionClaimedEvent {
+            mint: ctx.accounts.mint.key(),
+            user: ctx.accounts.user.key(),
+            committed: bid_amount,
+            token_amount: tokens,
+            sol_cost: cost_share,
+            refund,
+            user_position: holder_stats.entry_position,
+            timestamp: Clock::get()?.unix_timestamp,
+        };
+
+        emit_cpi!(event.clone());
+        emit!(event);
+
+        Ok(())
+    }
+}
+
+mod helpers {
+    use super::*;
+    use anchor_lang::solana_program::keccak;
+
+    /// Revoke Early Bird status permanently when user sells
+    /// Uses u64::MAX as sentinel value to prevent re-qualification on future buys
+    /// Also decrements the valid early bird count
+    pub fn revoke_early_bird_status(
+        holder_stats: &mut HolderStats,
+        bonding_curve: &mut BondingCurve
+    ) {
+        if holder_stats.entry_position > 0 && holder_stats.entry_position != u64::MAX {
+            // Check if this position was within the early bird cutoff
+            if holder_stats.entry_position <= bonding_curve.early_bird_cutoff {
+                // Decrement valid count since this early bird seat is now revoked
+                bonding_curve.early_bird_valid_count =
+                    bonding_curve.early_bird_valid_count.saturating_sub(1);
+
+                verbose_msg!(
+                    "💔 Early Bird status revoked! Position #{} forfeited FOREVER. Valid seats remaining: {}",
+                    holder_stats.entry_position,
+                    bonding_curve.early_bird_valid_count
+                );
+            }
+
+            holder_stats.entry_position = u64::MAX; // MAX = permanently revoked
+        }
+    }
+
+    /// Rejects stale orders that land after the signer's deadline or slot bound.
+    pub fn check_trade_deadline(deadline_unix_ts: Option<i64>, max_slot: Option<u64>) -> Result<()> {
+        if deadline_unix_ts.is_none() && max_slot.is_none() {
+            return Ok(());
+        }
+
+        let clock = Clock::get()?;
+        if let Some(deadline) = deadline_unix_ts {
+            require!(clock.unix_timestamp <= deadline, HorseFunError::TradeExpired);
+        }
+        if let Some(slot) = max_slot {
+            require!(clock.slot <= slot, HorseFunError::TradeExpired);
+        }
+
+        Ok(())
+    }
+
+    /// Checks a revealed trade against its commitment and closes it to the user,
+    /// returning rent and any escrowed SOL before the trade executes.
+    pub fn consume_trade_commitment<'info>(
+        trade_commitment: &Option<Account<'info, TradeCommitment>>,
+        user: &Signer<'info>,
+        global: &Global,
+        is_buy: bool,
+        amount: u64,
+        limit: u64,
+        salt: &[u8; 32]
+    ) -> Result<()> {
+        let trade_commitment = trade_commitment
+            .as_ref()
+            .ok_or(HorseFunError::InvalidTradeCommitment)?;
+        require_keys_eq!(trade_commitment.user, user.key(), HorseFunError::InvalidTradeCommitment);
+        require!(
+            Clock::get()?.slot >=
+                trade_commitment.committed_slot.saturating_add(global.commit_reveal_min_slots),
+            HorseFunError::RevealTooEarly
+        );
+
+        let hash = keccak::hashv(
+            &[&[is_buy as u8], &amount.to_le_bytes(), &limit.to_le_bytes(), salt]
+        );
+        require!(
+            hash.to_bytes() == trade_commitment.commitment,
+            HorseFunError::InvalidTradeCommitment
+        );
+
+        trade_commitment.close(user.to_account_info())
+    }
+
+    /// During the presale phase only allowlisted wallets may buy, up to the per-wallet cap.
+    /// Leaves are `keccak(wallet)`; pairs are hashed in sorted order.
+    pub fn check_presale(
+        bonding_curve: &BondingCurve,
+        holder_stats: &HolderStats,
+        user: &Pubkey,
+        token_amount: u64,
+        proof: &[[u8; 32]]
+    ) -> Result<()> {
+        let presale = bonding_curve.presale;
+        if presale.ends_at == 0 || Clock::get()?.unix_timestamp >= presale.ends_at {
+            return Ok(());
+        }
+
+        let mut node = keccak::hashv(&[user.as_ref()]).to_bytes();
+        for sibling in proof {
+            node = if node <= *sibling {
+                keccak::hashv(&[&node, sibling]).to_bytes()
+            } else {
+                keccak::hashv(&[sibling, &node]).to_bytes()
+            };
+        }
+        require!(node == presale.merkle_root, HorseFunError::NotOnAllowlist);
+
+        if presale.max_tokens_per_wallet > 0 {
+            require!(
+                holder_stats.current_balance.saturating_add(token_amount) <=
+                    presale.max_tokens_per_wallet,
+                HorseFunError::PresaleWalletCapExceeded
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Hash of the first block at or after `target_slot` in the SlotHashes sysvar, used as
+    /// the auction shuffle seed. None once the target has aged out of the sysvar.
+    pub fn slot_hash_at(slot_hashes: &AccountInfo, target_slot: u64) -> Result<Option<[u8; 32]>> {
+        let data = slot_hashes.try_borrow_data()?;
+        // Layout: u64 entry count, then (u64 slot, [u8; 32] hash) entries, newest first
+        require!(data.len() >= 8, HorseFunError::AuctionSeedPending);
+        let count = u64::from_le_bytes(data[..8].try_into().unwrap()) as usize;
+        let mut found = None;
+        for (i, entry) in data[8..].chunks_exact(40).take(count).enumerate() {
+            let slot = u64::from_le_bytes(entry[..8].try_into().unwrap());
+            if slot < target_slot {
+                // Nothing at or after the target has been recorded yet
+                require!(i > 0, HorseFunError::AuctionSeedPending);
+                return Ok(found);
+            }
+            let mut hash = [0u8; 32];
+            hash.copy_from_slice(&entry[8..]);
+            found = Some(hash);
+        }
+        // Every entry is newer than the target, which may have aged out
+        Ok(None)
+    }
+
+    /// Affine permutation `rank = (a * index + b) mod n` derived from `seed`, with gcd(a, n) = 1.
+    pub fn auction_shuffle_params(seed: &[u8; 32], bidder_count: u64) -> (u64, u64) {
+        if bidder_count <= 1 {
+            return (1, 0);
+        }
+        let word = |i: usize| u64::from_le_bytes(seed[i..i + 8].try_into().unwrap());
+        let mut a = (word(0) % bidder_count).max(1);
+        while gcd(a, bidder_count) != 1 {
+            a = (a % (bidder_count - 1)) + 1;
+        }
+        (a, word(8) % bidder_count)
+    }
+
+    pub fn auction_rank(auction: &OpeningAuction, bid_index: u64) -> u64 {
+        if auction.bidder_count == 0 {
+            return u64::MAX;
+        }
+        let n = auction.bidder_count as u128;
+        (((auction.shuffle_a as u128) * (bid_index as u128) + (auction.shuffle_b as u128)) % n) as u64
+    }
+
+    fn gcd(mut a: u64, mut b: u64) -> u64 {
+        while b != 0 {
+            let r = a % b;
+            a = b;
+            b = r;
+        }
+        a
+    }
+
+    /// Circuit breaker: fails the trade if it moved the price more than `price_band_bps`
+    /// away from the price the curve had at the start of this slot.
+    pub fn check_price_band(
+        bonding_curve: &BondingCurve,
+        global: &Global,
+        mint: Pubkey,
+        reference_price: u128
+    ) -> Result<()> {
+        let band_bps = global.price_band_bps;
+        if band_bps == 0 || reference_price == 0 {
+            return Ok(());
+        }
+
+        let price = bonding_curve.spot_price();
+        let deviation_bps = (price.abs_diff(reference_price) * 10_000) / reference_price;
+        if deviation_bps > (band_bps as u128) {
+            // Logged for monitoring; the transaction itself is rejected
+            emit!(CircuitBreakerTripped {
+                mint,
+                slot: bonding_curve.price_ref_slot,
+                reference_price,
+                attempted_price: price,
+                deviation_bps: deviation_bps as u64,
+                band_bps,
+                timestamp: Clock::get()?.unix_timestamp,
+            });
+            return err!(HorseFunError::PriceBandExceeded);
+        }
+
+        Ok(())
+    }
+
+    /// Fee shares are bps of the trading fee and must add up to exactly 10000.
+    /// Summed in u128 so oversized inputs cannot wrap around to 10000.
+    pub fn check_fee_shares(
+        creator_fee_share: u64,
+        platform_fee_share: u64,
+        treasury_fee_share: u64,
+        early_bird_fee_share: u64
+    ) -> Result<()> {
+        let total =
+            (creator_fee_share as u128) +
+            (platform_fee_share as u128) +
+            (treasury_fee_share as u128) +
+            (early_bird_fee_share as u128);
+        require!(total == 10_000, HorseFunError::InvalidFeeShares);
+        Ok(())
+    }
+
+    /// Rejects every state-changing instruction while the program is paused.
+    pub fn check_not_paused(global: &Global) -> Result<()> {
+        require!(!global.paused, HorseFunError::ProgramPaused);
+        Ok(())
+    }
+
+    /// Rejects trades on a frozen curve, a cancelled launch or before its scheduled start.
+    pub fn check_trading_open(bonding_curve: &BondingCurve) -> Result<()> {
+        require!(bonding_curve.frozen == 0, HorseFunError::CurveFrozen);
+        require!(bonding_curve.launch_cancelled == 0, HorseFunError::LaunchCancelled);
+        let auction = bonding_curve.auction;
+        require!(auction.ends_at == 0 || auction.settled != 0, HorseFunError::AuctionNotEnded);
+        require!(
+            Clock::get()?.unix_timestamp >= bonding_curve.trading_starts_at,
+            HorseFunError::TradingNotStarted
+        );
+        Ok(())
+    }
+
+    /// Resolves the `FeeOverride` that applies to a trade, if any.
+    /// A curve flagged with an active override must be traded with that account supplied.
+    pub fn active_fee_override(
+        bonding_curve: &BondingCurve,
+        fee_override: &Option<Account<FeeOverride>>
+    ) -> Result<Option<FeeOverride>> {
+        match fee_override {
+            Some(fee_override) if fee_override.enabled => Ok(Some(FeeOverride::clone(fee_override))),
+            _ => {
+                require!(bonding_curve.has_fee_override == 0, HorseFunError::FeeOverrideRequired);
+                Ok(None)
+            }
+        }
+    }
+
+    /// Enforces the anti-sniper caps while the curve is inside its launch window.
+    pub fn check_launch_protection(
+        bonding_curve: &BondingCurve,
+        holder_balance: u64,
+        token_amount: u64,
+        sol_cost: u64
+    ) -> Result<()> {
+        let protection = bonding_curve.launch_protection;
+        let slot = Clock::get()?.slot;
+        let window_end = bonding_curve
+            .launch_window_start(slot)
+            .saturating_add(protection.window_slots);
+        if protection.window_slots == 0 || slot >= window_end {
+            return Ok(());
+        }
+
+        if protection.max_sol_per_tx > 0 {
+            require!(sol_cost <= protection.max_sol_per_tx, HorseFunError::LaunchSolCapExceeded);
+        }
+        if protection.max_tokens_per_wallet > 0 {
+            require!(
+                holder_balance.saturating_add(token_amount) <= protection.max_tokens_per_wallet,
+                HorseFunError::LaunchWalletCapExceeded
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Share of the platform fee paid to the referrer, or 0 when the trade has none.
+    pub fn referral_fee_share(
+        global: &Global,
+        referral_vault: &Option<Account<ReferralVault>>
+    ) -> u64 {
+        if referral_vault.is_some() { global.referral_fee_share } else { 0 }
+    }
+
+    /// Applies a sized buy to the curve: reserves, transfers, fee pools, holder stats,
+    /// early bird tracking, buyback and the final TradeEvent.
+    /// Shared by `buy` and `buy_exact_sol_in` so both paths stay identical.
+    pub fn process_buy(
+        ctx: &mut Context<Buy>,
+        available_amount: u64,
+        sol_cost: u64,
+        fee: u64,
+        fee_discount_bps: u16
+    ) -> Result<()> {
+        let mut bonding_curve = ctx.accounts.bonding_curve.load_mut()?;
+
+        // Log initial fee state
+        verbose_msg!("Buy: Fee state before trade:");
+        verbose_msg!(" - Creator Fee Pool: {}", bonding_curve.creator_fee_pool);
+        verbose_msg!(" - Treasury Fee Pool: {}", bonding_curve.treasury_fee_pool);
+        verbose_msg!(" - Total Creator Fees Accrued: {}", bonding_curve.total_fees_accrued);
+        verbose_msg!(
+            " - Total Treasury Fees Accrued: {}",
+            bonding_curve.total_treasury_fees_accrued
+        );
+
+        require_keys_eq!(
+            ctx.accounts.associated_bonding_curve.mint,
+            ctx.accounts.mint.key(),
+            HorseFunError::MintDoesNotMatchBondingCurve
+        );
+        require!(bonding_curve.complete == 0, HorseFunError::BondingCurveComplete);
+        check_not_paused(&ctx.accounts.global)?;
+        check_trading_open(&bonding_curve)?;
+
+        check_launch_protection(
+            &bonding_curve,
+            ctx.accounts.holder_stats.current_balance,
+            available_amount,
+            sol_cost
+        )?;
+        if bonding_curve.trading_opened_slot == 0 {
+            bonding_curve.trading_opened_slot = Clock::get()?.slot;
+        }
+
+        verbose_msg!(" - Amount: {}", available_amount);
+        verbose_msg!(" - virtual token reserves: {}", bonding_curve.virtual_token_reserves);
+        verbose_msg!(" - Real Token Reserves: {}", bonding_curve.real_token_reserves);
+
+        let reference_price = bonding_curve.slot_reference_price(Clock::get()?.slot);
+
+        // Update virtual reserves (these track the theoretical price curve, including fees)
+        bonding_curve.virtual_token_reserves -= available_amount;
+        bonding_curve.virtual_sol_reserves += sol_cost;
+
+        // Update real reserves (actual tokens and SOL in the curve)
+        bonding_curve.real_token_reserves -= available_amount;
+        // Only add the actual SOL cost to reserves, fees are tracked separately in fee pools
+        bonding_curve.real_sol_reserves += sol_cost;
+
+        check_price_band(
+            &bonding_curve,
+            &ctx.accounts.global,
+            ctx.accounts.mint.key(),
+            reference_price
+        )?;
+
+        if bonding_curve.real_token_reserves == 0 {
+            bonding_curve.complete = 1;
+
+            // ⭐ Calculate and cache equal share for early bird rewards
+            // This ensures all early birds get EXACTLY the same amount
+            let valid_count = bonding_curve.early_bird_valid_count;
+            if valid_count > 0 && bonding_curve.early_bird_pool > 0 {
+                bonding_curve.early_bird_share_per_seat =
+                    bonding_curve.early_bird_pool / valid_count;
+                verbose_msg!(
+                    "🐦 Early Bird rewards locked: {} valid seats, {} lamports per seat",
+                    valid_count,
+                    bonding_curve.early_bird_share_per_seat
+                );
+            }
+
+            emit_cpi!(CompleteEvent {
+                mint: ctx.accounts.mint.key(),
+                user: ctx.accounts.user.key(),
+                bonding_curve: ctx.accounts.bonding_curve.key(),
+                timestamp: Clock::get()?.unix_timestamp,
+                early_bird_pool: bonding_curve.early_bird_pool,
+            });
+
+            emit!(CompleteEvent {
+                mint: ctx.accounts.mint.key(),
+                user: ctx.accounts.user.key(),
+                bonding_curve: ctx.accounts.bonding_curve.key(),
+                timestamp: Clock::get()?.unix_timestamp,
+                early_bird_pool: bonding_curve.early_bird_pool,
+            });
+        }
+
+        // The curve takes part in the transfers below, so release it until they are done
+        drop(bonding_curve);
+
+        // transfer the capped amount using the helper
+        helpers::transfer_tokens_from_bonding_curve_to_user(ctx, available_amount)?;
+
+        // transfer the sol from the user to the bonding curve (only the actual cost, not including fee)
+        helpers::transfer_sol_from_user_to_bonding_curve(ctx, sol_cost)?;
+
+        // transfer the fee separately from user to fee recipient and update fee pools
+        helpers::transfer_sol_from_user_to_fee_recipient(ctx, fee)?;
+
+        let mut bonding_curve = ctx.accounts.bonding_curve.load_mut()?;
+
+        // Log final fee state
+        verbose_msg!("Buy: Fee state after trade:");
+        verbose_msg!(" - Creator Fee Pool: {}", bonding_curve.creator_fee_pool);
+        verbose_msg!(" - Treasury Fee Pool: {}", bonding_curve.treasury_fee_pool);
+        verbose_msg!(" - Early Bird Pool: {}", bonding_curve.early_bird_pool);
+        verbose_msg!(" - Total Creator Fees Accrued: {}", bonding_curve.total_fees_accrued);
+        verbose_msg!(
+            " - Total Treasury Fees Accrued: {}",
+            bonding_curve.total_treasury_fees_accrued
+        );
+
+        // Update holder stats and track entry position for early bird rewards
+        ctx.accounts.holder_stats.current_balance = ctx.accounts.holder_stats.current_balance
+            .checked_add(available_amount)
+            .ok_or(HorseFunError::ArithmeticOverflow)?;
+        ctx.accounts.holder_stats.total_volume += sol_cost;
+
+        // Track entry position if this is the first buy (for early bird rewards)
+        // Note: entry_position == 0 means first-time buyer
+        //       entry_position == u64::MAX means revoked/disqualified (sold before)
+        // Anti-Sybil: Requires minimum SOL buy amount to prevent 50-wallet gaming
+        if ctx.accounts.holder_stats.entry_position == 0 {
+            // Check if buy amount meets minimum threshold for Early Bird eligibility
+            if sol_cost >= ctx.accounts.global.early_bird_min_buy_sol {
+                bonding_curve.total_buyers += 1;
+                ctx.accounts.holder_stats.entry_position = bonding_curve.total_buyers;
+
+                // Log early bird status
+                if
+                    ctx.accounts.global.early_bird_enabled &&
+                    ctx.accounts.holder_stats.entry_position <=
+                        bonding_curve.early_bird_cutoff
+                {
+                    bonding_curve.early_bird_valid_count += 1;
+                    verbose_msg!(
+                        "🐦 Early Bird #{}/{}! User will earn rewards from future trades!",
+                        ctx.accounts.holder_stats.entry_position,
+                        bonding_curve.early_bird_cutoff
+                    );
+                }
+            } else {
+                verbose_msg!(
+                    "⚠️ Buy amount {} lamports is below minimum {} lamports for Early Bird eligibility",
+                    sol_cost,
+                    ctx.accounts.global.early_bird_min_buy_sol
+                );
+            }
+        } else if ctx.accounts.holder_stats.entry_position == u64::MAX {
+            verbose_msg!(
+                "🚫 User is permanently disqualified from Early Bird rewards (sold previously)"
+            );
+        }
+
+        // Initialize buyback-related values with defaults (no buyback occurred yet)
+        let mut is_buyback = false;
+        let mut burn_amount = 0;
+        let mut price_lamports_per_token = 0;
+
+        if ctx.accounts.global.buybacks_enabled {
+            // ---- Dynamic (EMA + Backing) Buyback: sizing lives in BondingCurve::plan_buyback ----
+            let params = bonding_curve.buyback_params();
+            if let Some(plan) = bonding_curve.plan_buyback(&params)? {
+                let amount = plan.amount;
+                let bb_cost = plan.cost;
+
+                // Snapshot pre-state for delta logs
+                let vtr0 = bonding_curve.virtual_token_reserves;
+                let rtr0 = bonding_curve.real_token_reserves;
+                let vsr0 = bonding_curve.virtual_sol_reserves;
+                let rsr0 = bonding_curve.real_sol_reserves;
+                let tpool0 = bonding_curve.treasury_fee_pool;
+
+                // Spend from treasury pool and apply the same state transition as a buy
+                bonding_curve.apply_buyback(&plan);
+
+                // Intended burn sizing = full buyback size
+                let curve_ata_bal = ctx.accounts.associated_bonding_curve.amount;
+                let mint_supply_before = ctx.accounts.mint.supply;
+
+                verbose_msg!(
+                    "BB[burn-plan]: burn_amount={} curve_ata_bal={} mint_supply_before={}",
+                    amount,
+                    curve_ata_bal,
+                    mint_supply_before
+                );
+
+                // Safety guard
+                let burn_ok = amount > 0 && curve_ata_bal >= amount;
+                verbose_msg!(
+                    "BB[burn-guard]: curve_ata>=intended?={} ({}>=?)",
+                    burn_ok,
+                    curve_ata_bal
+                );
+
+                // ---- BURN ENABLED ----
+                if burn_ok {
+                    // The curve PDA signs the burn, so its data must not be borrowed
+                    drop(bonding_curve);
+                    helpers::burn_from_curve_ata_on_buy(ctx, amount)?;
+                    bonding_curve = ctx.accounts.bonding_curve.load_mut()?;
+
+                    // IMPORTANT: do NOT subtract real_token_reserves again here.
+                    // apply_buyback already reduced it by `amount`.
+                    // Update circulating supply and burn totals when tokens are burned
+                    bonding_curve.circulating_supply =
+                        bonding_curve.circulating_supply.saturating_sub(amount);
+                    bonding_curve.total_burned_supply =
+                        bonding_curve.total_burned_supply.saturating_add(amount);
+                }
+
+                // Update buyback variables for TradeEvent at end of function
+                is_buyback = true;
+                burn_amount = if burn_ok { amount } else { 0 };
+                price_lamports_per_token = bb_cost.saturating_div(amount);
+
+                // Delta log
+                verbose_msg!(
+                    "BB[go]: amount={} cost={} new_tpool={}",
+                    amount,
+                    bb_cost,
+                    bonding_curve.treasury_fee_pool
+                );
+                verbose_msg!(
+                    "BB[delta]: Δvtok=-{} Δrtok=-{} Δvsol=+{} Δrsol=+{} Δtpool=-{}",
+                    vtr0.saturating_sub(bonding_curve.virtual_token_reserves),
+                    rtr0.saturating_sub(bonding_curve.real_token_reserves),
+                    bonding_curve.virtual_sol_reserves.saturating_sub(vsr0),
+                    bonding_curve.real_sol_reserves.saturating_sub(rsr0),
+                    tpool0.saturating_sub(bonding_curve.treasury_fee_pool)
+                );
+            }
+        } else {
+            verbose_msg!("Buybacks are disabled globally");
+        }
+
+        // Calculate fee splits for this trade to emit in event
+        let fee_override = helpers::active_fee_override(
+            &bonding_curve,
+            &ctx.accounts.fee_override
+        )?;
+        let referral_fee_share = referral_fee_share(
+            &ctx.accounts.global,
+            &ctx.accounts.referral_vault
+        );
+        let (_, creator_fee_for_trade, _, _, referral_fee_for_trade) =
+            bonding_curve.get_fee_splits(fee, fee_override.as_ref(), referral_fee_share);
+
+        // 🔧 Calculate if this user is a valid early bird
+        let user_pos = ctx.accounts.holder_stats.entry_position;
+        let is_early_bird =
+            user_pos > 0 &&
+            user_pos != u64::MAX &&
+            user_pos <= bonding_curve.early_bird_cutoff;
+
+        // Emit final TradeEvent with all information (including possible buyback)
+        let trade_event = TradeEvent {
+            user: ctx.accounts.user.key(),
+            sol_amount: sol_cost,
+            token_amount: available_amount,
+            is_buy: true,
+            mint: ctx.accounts.mint.key(),
+            timestamp: Clock::get()?.unix_timestamp,
+            virtual_sol_reserves: bonding_curve.virtual_sol_reserves,
+            virtual_token_reserves: bonding_curve.virtual_token_reserves,
+            circulating_supply: bonding_curve.circulating_supply,
+            real_token_reserves: bonding_curve.real_token_reserves,
+            real_sol_reserves: bonding_curve.real_sol_reserves,
+            creator_fee_pool: bonding_curve.creator_fee_pool,
+            treasury_fee_pool: bonding_curve.treasury_fee_pool,
+            total_fees_accrued: bonding_curve.total_fees_accrued,
+            total_treasury_fees_accrued: bonding_curve.total_treasury_fees_accrued,
+            creator_fee_amount: creator_fee_for_trade, // Fee earned by creator from THIS trade
+            fee_recipient: bonding_curve.creator_wallet, // Current creator (CTO-aware)
+            is_buyback,
+            burn_amount,
+            price_lamports_per_token,
+            total_burned_supply: bonding_curve.total_burned_supply,
+            total_treasury_spent: bonding_curve.total_treasury_spent,
+            early_bird_pool: bonding_curve.early_bird_pool,
+            total_early_bird_fees_accrued: bonding_curve.total_early_bird_fees_accrued,
+            user_position: ctx.accounts.holder_stats.entry_position,
+            user_balance: ctx.accounts.holder_stats.current_balance,
+            early_bird_cutoff: bonding_curve.early_bird_cutoff,
+            total_buyers: bonding_curve.total_buyers,
+            early_bird_valid_count: bonding_curve.early_bird_valid_count,
+            is_early_bird,
+            referrer: ctx.accounts.referral_vault.as_ref().map(|vault| vault.referrer),
+            referral_fee_amount: referral_fee_for_trade,
+            fee_discount_bps,
+            is_creator_buy: false,
+        };
+        drop(bonding_curve);
+
+        emit_cpi!(trade_event);
+        emit!(trade_event);
+
+        Ok(())
+    }
+
+    /// Creator dev-buy executed inside `create`: sizes and applies the first purchase,
+    /// initializes the creator's HolderStats and emits a TradeEvent flagged `is_creator_buy`.
+    /// Buybacks are skipped since the treasury pool is empty on a fresh curve.
+    pub fn process_creator_buy(
+        ctx: &mut Context<Create>,
+        amount: u64,
+        max_sol_cost: u64
+    ) -> Result<()> {
+        require!(amount > 0, HorseFunError::InvalidAmount);
+
+        let accounts = &mut ctx.accounts;
+        // The curve was initialized earlier in this instruction and its discriminator is
+        // only written on exit, so it is still reached through `load_init`
+        let mut bonding_curve = accounts.bonding_curve.load_init()?;
+
+        // The dev-buy must not jump the presale, auction or scheduled start it configured
+        require!(
+            bonding_curve.presale.ends_at == 0 &&
+                bonding_curve.auction.ends_at == 0 &&
+                bonding_curve.trading_starts_at <= Clock::get()?.unix_timestamp,
+            HorseFunError::CreatorBuyNotAllowed
+        );
+
+        // The creator cannot buy out the whole curve in the launch transaction
+        require!(amount < bonding_curve.real_token_reserves, HorseFunError::InvalidAmount);
+
+        let sol_cost = bonding_curve
+            .buy_quote_checked(amount)
+            .ok_or(HorseFunError::CurveCannotFill)?;
+        let fee = bonding_curve.get_fee(sol_cost, None, 0);
+        require!(sol_cost.saturating_add(fee) <= max_sol_cost, HorseFunError::TooMuchSolRequired);
+
+        let associated_user = accounts.associated_user
+            .as_ref()
+            .ok_or(HorseFunError::MissingCreatorBuyAccounts)?;
+        let fee_recipient = accounts.fee_recipient
+            .as_ref()
+            .ok_or(HorseFunError::MissingCreatorBuyAccounts)?;
+        let holder_stats = accounts.holder_stats
+            .as_mut()
+            .ok_or(HorseFunError::MissingCreatorBuyAccounts)?;
+        require_keys_eq!(
+            accounts.global.fee_recipient,
+            fee_recipient.key(),
+            HorseFunError::NotAuthorized
+        );
 
-                msg!(
-                    "💔 Early Bird status revoked! Position #{} forfeited FOREVER. Valid seats remaining: {}",
-                    holder_stats.entry_position,
-                    bonding_curve.early_bird_valid_count
-                );
+        holder_stats.user = accounts.user.key();
+        holder_stats.mint = accounts.mint.key();
+        holder_stats.fees_claimed = 0;
+        holder_stats.current_balance = 0;
+        holder_stats.total_volume = 0;
+        holder_stats.version = HolderStats::VERSION;
+        check_launch_protection(&bonding_curve, holder_stats.current_balance, amount, sol_cost)?;
+
+        verbose_msg!("Creator dev-buy: amount={} sol_cost={} fee={}", amount, sol_cost, fee);
+
+        let reference_price = bonding_curve.slot_reference_price(Clock::get()?.slot);
+
+        // Reserves
+        bonding_curve.virtual_token_reserves -= amount;
+        bonding_curve.virtual_sol_reserves += sol_cost;
+        bonding_curve.real_token_reserves -= amount;
+        bonding_curve.real_sol_reserves += sol_cost;
+
+        check_price_band(&bonding_curve, &accounts.global, accounts.mint.key(), reference_price)?;
+
+        // Fee pools: creator/treasury/early bird stay on the curve
+        let (platform_fee, creator_fee, treasury_fee, early_bird_fee, _) =
+            bonding_curve.get_fee_splits(fee, None, 0);
+        bonding_curve.accrue_fees(creator_fee, treasury_fee, early_bird_fee);
+
+        // Holder stats; the creator seat is either counted or permanently excluded
+        holder_stats.current_balance = amount;
+        holder_stats.total_volume = sol_cost;
+        if accounts.global.creator_buy_counts_as_early_bird {
+            if sol_cost >= accounts.global.early_bird_min_buy_sol {
+                bonding_curve.total_buyers += 1;
+                holder_stats.entry_position = bonding_curve.total_buyers;
+                if
+                    accounts.global.early_bird_enabled &&
+                    holder_stats.entry_position <= bonding_curve.early_bird_cutoff
+                {
+                    bonding_curve.early_bird_valid_count += 1;
+                }
             }
+        } else {
+            holder_stats.entry_position = u64::MAX;
+        }
 
-            holder_stats.entry_position = u64::MAX; // MAX = permanently revoked
+        let user_pos = holder_stats.entry_position;
+        let is_early_bird =
+            user_pos > 0 && user_pos != u64::MAX && user_pos <= bonding_curve.early_bird_cutoff;
+
+        let mint_key = accounts.mint.key();
+        let trade_event = TradeEvent {
+            user: accounts.user.key(),
+            sol_amount: sol_cost,
+            token_amount: amount,
+            is_buy: true,
+            mint: mint_key,
+            timestamp: Clock::get()?.unix_timestamp,
+            virtual_sol_reserves: bonding_curve.virtual_sol_reserves,
+            virtual_token_reserves: bonding_curve.virtual_token_reserves,
+            circulating_supply: bonding_curve.circulating_supply,
+            real_token_reserves: bonding_curve.real_token_reserves,
+            real_sol_reserves: bonding_curve.real_sol_reserves,
+            creator_fee_pool: bonding_curve.creator_fee_pool,
+            treasury_fee_pool: bonding_curve.treasury_fee_pool,
+            total_fees_accrued: bonding_curve.total_fees_accrued,
+            total_treasury_fees_accrued: bonding_curve.total_treasury_fees_accrued,
+            creator_fee_amount: creator_fee,
+            fee_recipient: bonding_curve.creator_wallet,
+            is_buyback: false,
+            burn_amount: 0,
+            price_lamports_per_token: 0,
+            total_burned_supply: bonding_curve.total_burned_supply,
+            total_treasury_spent: bonding_curve.total_treasury_spent,
+            early_bird_pool: bonding_curve.early_bird_pool,
+            total_early_bird_fees_accrued: bonding_curve.total_early_bird_fees_accrued,
+            user_position: user_pos,
+            user_balance: holder_stats.current_balance,
+            early_bird_cutoff: bonding_curve.early_bird_cutoff,
+            total_buyers: bonding_curve.total_buyers,
+            early_bird_valid_count: bonding_curve.early_bird_valid_count,
+            is_early_bird,
+            referrer: None,
+            referral_fee_amount: 0,
+            fee_discount_bps: 0,
+            is_creator_buy: true,
+        };
+
+        // State is final; release the curve before it takes part in the CPIs below
+        drop(bonding_curve);
+
+        // Tokens: curve ATA → creator ATA, signed by the curve PDA
+        let authority_seed = &[
+            b"bonding-curve".as_ref(),
+            mint_key.as_ref(),
+            &[ctx.bumps.bonding_curve],
+        ];
+        let seeds = [authority_seed.as_slice()];
+        token::transfer(
+            CpiContext::new_with_signer(
+                accounts.token_program.to_account_info(),
+                token::Transfer {
+                    from: accounts.associated_bonding_curve.to_account_info(),
+                    to: associated_user.to_account_info(),
+                    authority: accounts.bonding_curve.to_account_info(),
+                },
+                &seeds
+            ),
+            amount
+        )?;
+
+        // SOL: cost + creator/treasury/early bird fees to the curve, platform fee to recipient
+        transfer(
+            CpiContext::new(accounts.system_program.to_account_info(), Transfer {
+                from: accounts.user.to_account_info(),
+                to: accounts.bonding_curve.to_account_info(),
+            }),
+            sol_cost + creator_fee + treasury_fee + early_bird_fee
+        )?;
+        if platform_fee > 0 {
+            transfer(
+                CpiContext::new(accounts.system_program.to_account_info(), Transfer {
+                    from: accounts.user.to_account_info(),
+                    to: fee_recipient.to_account_info(),
+                }),
+                platform_fee
+            )?;
+        }
+
+        emit_cpi!(trade_event);
+        emit!(trade_event);
+
+        Ok(())
+    }
+
+    /// Applies a sized sell to the curve: reserves, transfers, fee pools, holder stats,
+    /// early bird revocation, buyback and the final TradeEvent.
+    /// Shared by `sell` and `sell_exact_sol_out` so both paths stay identical.
+    pub fn process_sell(
+        ctx: &mut Context<Sell>,
+        amount: u64,
+        sol_output: u64,
+        fee: u64,
+        fee_discount_bps: u16
+    ) -> Result<()> {
+        let mut bonding_curve = ctx.accounts.bonding_curve.load_mut()?;
+
+        // Log initial fee state
+        verbose_msg!("Sell: Fee state before trade:");
+        verbose_msg!(" - Creator Fee Pool: {}", bonding_curve.creator_fee_pool);
+        verbose_msg!(" - Treasury Fee Pool: {}", bonding_curve.treasury_fee_pool);
+        verbose_msg!(" - Total Creator Fees Accrued: {}", bonding_curve.total_fees_accrued);
+        verbose_msg!(
+            " - Total Treasury Fees Accrued: {}",
+            bonding_curve.total_treasury_fees_accrued
+        );
+
+        // Initialize buyback-related values with defaults (no buyback occurred yet)
+        let mut is_buyback = false;
+        let mut burn_amount = 0;
+        let mut price_lamports_per_token = 0;
+
+        require_keys_eq!(
+            ctx.accounts.associated_bonding_curve.mint,
+            ctx.accounts.mint.key(),
+            HorseFunError::MintDoesNotMatchBondingCurve
+        );
+        require!(bonding_curve.complete == 0, HorseFunError::BondingCurveComplete);
+        check_not_paused(&ctx.accounts.global)?;
+        check_trading_open(&bonding_curve)?;
+
+        let reference_price = bonding_curve.slot_reference_price(Clock::get()?.slot);
+
+        // update the bonding curve parameters (excluding fee)
+        bonding_curve.virtual_token_reserves =
+            bonding_curve.virtual_token_reserves.saturating_add(amount);
+        bonding_curve.real_token_reserves =
+            bonding_curve.real_token_reserves.saturating_add(amount);
+        bonding_curve.virtual_sol_reserves =
+            bonding_curve.virtual_sol_reserves.saturating_sub(sol_output);
+        // Only reduce by actual transfer amount (user receives sol_output - fee)
+        bonding_curve.real_sol_reserves =
+            bonding_curve.real_sol_reserves.saturating_sub(sol_output.saturating_sub(fee));
+
+        check_price_band(
+            &bonding_curve,
+            &ctx.accounts.global,
+            ctx.accounts.mint.key(),
+            reference_price
+        )?;
+
+        // transfer the tokens from the user to the bonding curve
+        helpers::transfer_tokens_from_user_to_bonding_curve(ctx, amount)?;
+
+        // Log final fee state
+        verbose_msg!("Sell: Fee state after trade:");
+        verbose_msg!(" - Creator Fee Pool: {}", bonding_curve.creator_fee_pool);
+        verbose_msg!(" - Treasury Fee Pool: {}", bonding_curve.treasury_fee_pool);
+        verbose_msg!(" - Early Bird Pool: {}", bonding_curve.early_bird_pool);
+        verbose_msg!(" - Total Creator Fees Accrued: {}", bonding_curve.total_fees_accrued);
+        verbose_msg!(
+            " - Total Treasury Fees Accrued: {}",
+            bonding_curve.total_treasury_fees_accrued
+        );
+
+        // Calculate new balance by subtracting the tokens just sold from the current balance
+        // (Anchor's cached account might not reflect the latest transfer yet)
+        ctx.accounts.holder_stats.current_balance = ctx.accounts.holder_stats.current_balance
+            .checked_sub(amount)
+            .ok_or(HorseFunError::ArithmeticOverflow)?;
+        helpers::revoke_early_bird_status(
+            &mut ctx.accounts.holder_stats,
+            &mut bonding_curve
+        );
+
+        // Process buyback if enabled
+        if ctx.accounts.global.buybacks_enabled {
+            // ---- Dynamic (EMA + Backing) Buyback: sizing lives in BondingCurve::plan_buyback ----
+            let params = bonding_curve.buyback_params();
+            if let Some(plan) = bonding_curve.plan_buyback(&params)? {
+                let amount = plan.amount;
+                let bb_cost = plan.cost;
+
+                // Snapshot pre-state for delta logs
+                let vtr0 = bonding_curve.virtual_token_reserves;
+                let rtr0 = bonding_curve.real_token_reserves;
+                let vsr0 = bonding_curve.virtual_sol_reserves;
+                let rsr0 = bonding_curve.real_sol_reserves;
+                let tpool0 = bonding_curve.treasury_fee_pool;
+
+                // Spend from treasury pool and apply the same state transition as a buy
+                bonding_curve.apply_buyback(&plan);
+
+                // Intended burn sizing = full buyback size
+                let curve_ata_bal = ctx.accounts.associated_bonding_curve.amount;
+                let mint_supply_before = ctx.accounts.mint.supply;
+
+                verbose_msg!(
+                    "SBB[burn-plan]: burn_amount={} curve_ata_bal={} mint_supply_before={}",
+                    amount,
+                    curve_ata_bal,
+                    mint_supply_before
+                );
+
+                // Safety guard
+                let burn_ok = amount > 0 && curve_ata_bal >= amount;
+                verbose_msg!(
+                    "SBB[burn-guard]: curve_ata>=intended?={} ({}>=?)",
+                    burn_ok,
+                    curve_ata_bal
+                );
+
+                // ---- BURN ENABLED ----
+                if burn_ok {
+                    // The curve PDA signs the burn, so its data must not be borrowed
+                    drop(bonding_curve);
+                    helpers::burn_from_curve_ata_on_sell(ctx, amount)?;
+                    bonding_curve = ctx.accounts.bonding_curve.load_mut()?;
+
+                    // IMPORTANT: do NOT subtract real_token_reserves again here.
+                    // apply_buyback already reduced it by `amount`.
+                    // Update circulating supply and burn totals when tokens are burned
+                    bonding_curve.circulating_supply =
+                        bonding_curve.circulating_supply.saturating_sub(amount);
+                    bonding_curve.total_burned_supply =
+                        bonding_curve.total_burned_supply.saturating_add(amount);
+                }
+
+                // Update buyback variables for TradeEvent at end of function
+                is_buyback = true;
+                burn_amount = if burn_ok { amount } else { 0 };
+                price_lamports_per_token = bb_cost.saturating_div(amount);
+
+                // Delta log
+                verbose_msg!(
+                    "BB[go]: amount={} cost={} new_tpool={}",
+                    amount,
+                    bb_cost,
+                    bonding_curve.treasury_fee_pool
+                );
+                verbose_msg!(
+                    "BB[delta]: Δvtok=-{} Δrtok=-{} Δvsol=+{} Δrsol=+{} Δtpool=-{}",
+                    vtr0.saturating_sub(bonding_curve.virtual_token_reserves),
+                    rtr0.saturating_sub(bonding_curve.real_token_reserves),
+                    bonding_curve.virtual_sol_reserves.saturating_sub(vsr0),
+                    bonding_curve.real_sol_reserves.saturating_sub(rsr0),
+                    tpool0.saturating_sub(bonding_curve.treasury_fee_pool)
+                );
+            }
+        } else {
+            verbose_msg!("Buybacks are disabled globally");
         }
+        drop(bonding_curve);
+        verbose_msg!(
+            "SELL[payout-plan]: sol_output={} fee={} user_gets={}",
+            sol_output,
+            fee,
+            sol_output.saturating_sub(fee)
+        );
+
+        // transfer the sol from the bonding curve to the user (excluding fee)
+        helpers::transfer_sol_from_bonding_curve_to_user(ctx, sol_output.saturating_sub(fee))?;
+
+        // handle fee distribution from the retained amount
+        helpers::transfer_sol_from_bonding_curve_to_fee_recipient(ctx, fee)?;
+
+        // Calculate fee splits for this trade to emit in event
+        let bonding_curve = ctx.accounts.bonding_curve.load()?;
+        let fee_override = helpers::active_fee_override(
+            &bonding_curve,
+            &ctx.accounts.fee_override
+        )?;
+        let referral_fee_share = referral_fee_share(
+            &ctx.accounts.global,
+            &ctx.accounts.referral_vault
+        );
+        let (_, creator_fee_for_trade, _, _, referral_fee_for_trade) =
+            bonding_curve.get_fee_splits(fee, fee_override.as_ref(), referral_fee_share);
+
+        // 🔧 Calculate if this user is a valid early bird
+        let user_pos = ctx.accounts.holder_stats.entry_position;
+        let is_early_bird =
+            user_pos > 0 &&
+            user_pos != u64::MAX &&
+            user_pos <= bonding_curve.early_bird_cutoff;
+
+        // Emit final TradeEvent with all information (including possible buyback)
+        let trade_event = TradeEvent {
+            user: ctx.accounts.user.key(),
+            sol_amount: sol_output,
+            token_amount: amount,
+            is_buy: false,
+            mint: ctx.accounts.mint.key(),
+            timestamp: Clock::get()?.unix_timestamp,
+            virtual_sol_reserves: bonding_curve.virtual_sol_reserves,
+            virtual_token_reserves: bonding_curve.virtual_token_reserves,
+            circulating_supply: bonding_curve.circulating_supply,
+            real_token_reserves: bonding_curve.real_token_reserves,
+            real_sol_reserves: bonding_curve.real_sol_reserves,
+            creator_fee_pool: bonding_curve.creator_fee_pool,
+            treasury_fee_pool: bonding_curve.treasury_fee_pool,
+            total_fees_accrued: bonding_curve.total_fees_accrued,
+            total_treasury_fees_accrued: bonding_curve.total_treasury_fees_accrued,
+            creator_fee_amount: creator_fee_for_trade, // Fee earned by creator from THIS trade
+            fee_recipient: bonding_curve.creator_wallet, // Current creator (CTO-aware)
+            is_buyback,
+            burn_amount,
+            price_lamports_per_token,
+            total_burned_supply: bonding_curve.total_burned_supply,
+            total_treasury_spent: bonding_curve.total_treasury_spent,
+            early_bird_pool: bonding_curve.early_bird_pool,
+            total_early_bird_fees_accrued: bonding_curve.total_early_bird_fees_accrued,
+            user_position: ctx.accounts.holder_stats.entry_position,
+            user_balance: ctx.accounts.holder_stats.current_balance,
+            early_bird_cutoff: bonding_curve.early_bird_cutoff,
+            total_buyers: bonding_curve.total_buyers,
+            early_bird_valid_count: bonding_curve.early_bird_valid_count,
+            is_early_bird,
+            referrer: ctx.accounts.referral_vault.as_ref().map(|vault| vault.referrer),
+            referral_fee_amount: referral_fee_for_trade,
+            fee_discount_bps,
+            is_creator_buy: false,
+        };
+        drop(bonding_curve);
+
+        emit_cpi!(trade_event);
+        emit!(trade_event);
+
+        Ok(())
+    }
+
+    /// Grows a borsh account to `new_len` and re-serializes it after `migrate` has filled
+    /// in the appended fields. Returns `migrate`'s (from_version, to_version).
+    pub fn migrate_borsh_account<'info, T: AccountSerialize + AccountDeserialize>(
+        account: &AccountInfo<'info>,
+        payer: &AccountInfo<'info>,
+        system_program: &AccountInfo<'info>,
+        new_len: usize,
+        migrate: fn(&mut T) -> (u8, u8)
+    ) -> Result<(u8, u8)> {
+        realloc_account(account, payer, system_program, new_len)?;
+
+        // Checks the discriminator, so only accounts of type T get through
+        let mut data = T::try_deserialize(&mut &account.try_borrow_data()?[..])?;
+        let versions = migrate(&mut data);
+        data.try_serialize(&mut &mut account.try_borrow_mut_data()?[..])?;
+        Ok(versions)
     }
 
     #[inline]
+    /// Grows a program-owned account to `new_len` (zero-filled), topping up rent from `payer`.
+    /// Does nothing if the account is already large enough.
+    pub fn realloc_account<'info>(
+        account: &AccountInfo<'info>,
+        payer: &AccountInfo<'info>,
+        system_program: &AccountInfo<'info>,
+        new_len: usize
+    ) -> Result<()> {
+        require_keys_eq!(*account.owner, crate::ID, HorseFunError::NotAuthorized);
+        if account.data_len() >= new_len {
+            return Ok(());
+        }
+
+        let rent_due = Rent::get()?.minimum_balance(new_len).saturating_sub(account.lamports());
+        if rent_due > 0 {
+            transfer(
+                CpiContext::new(system_program.clone(), Transfer {
+                    from: payer.clone(),
+                    to: account.clone(),
+                }),
+                rent_due
+            )?;
+        }
+
+        account.realloc(new_len, true)?;
+        Ok(())
+    }
+
     pub fn pda_transfer_lamports(
         from: &AccountInfo,
         to: &AccountInfo,
@@ -1835,17 +3798,31 @@ mod helpers {
             HorseFunError::NotAuthorized
         );
 
-        // Split fees (4-way split now)
-        let (platform_fee, creator_fee, treasury_fee, early_bird_fee) =
-            ctx.accounts.global.get_fee_splits(sol_amount);
+        // Split fees (4-way split now), honouring any per-mint override
+        let mut bonding_curve = ctx.accounts.bonding_curve.load_mut()?;
+        let fee_override = active_fee_override(&bonding_curve, &ctx.accounts.fee_override)?;
+        let referral_fee_share = referral_fee_share(
+            &ctx.accounts.global,
+            &ctx.accounts.referral_vault
+        );
+        let (platform_fee, creator_fee, treasury_fee, early_bird_fee, referral_fee) =
+            bonding_curve.get_fee_splits(sol_amount, fee_override.as_ref(), referral_fee_share);
 
         // Book-keep fee pools (creator/treasury/early_bird stay on curve)
-        ctx.accounts.bonding_curve.creator_fee_pool += creator_fee;
-        ctx.accounts.bonding_curve.treasury_fee_pool += treasury_fee;
-        ctx.accounts.bonding_curve.early_bird_pool += early_bird_fee;
-        ctx.accounts.bonding_curve.total_fees_accrued += creator_fee;
-        ctx.accounts.bonding_curve.total_treasury_fees_accrued += treasury_fee;
-        ctx.accounts.bonding_curve.total_early_bird_fees_accrued += early_bird_fee;
+        bonding_curve.accrue_fees(creator_fee, treasury_fee, early_bird_fee);
+        drop(bonding_curve);
+
+        // Move the referral cut from curve PDA → referrer's vault
+        if let Some(vault) = ctx.accounts.referral_vault.as_mut() {
+            if referral_fee > 0 {
+                helpers::pda_transfer_lamports(
+                    &ctx.accounts.bonding_curve.to_account_info(),
+                    &vault.to_account_info(),
+                    referral_fee
+                )?;
+                vault.accrue(referral_fee);
+            }
+        }
 
         // Move platform fee from curve PDA → platform using invoke_signed
         if platform_fee > 0 {
@@ -1949,7 +3926,7 @@ mod helpers {
         ctx: &Context<Buy>,
         sol_amount: u64
     ) -> Result<()> {
-        msg!("Transferring {} SOL from user to bonding curve", sol_amount);
+        verbose_msg!("Transferring {} SOL from user to bonding curve", sol_amount);
         // transfer sol to bonding curve (excluding fees)
         transfer(
             CpiContext::new(ctx.accounts.system_program.to_account_info(), Transfer {
@@ -1971,17 +3948,33 @@ mod helpers {
             HorseFunError::NotAuthorized
         );
 
-        // Split fees according to global parameters (4-way split)
-        let (platform_fee, creator_fee, treasury_fee, early_bird_fee) =
-            ctx.accounts.global.get_fee_splits(fee_amount);
+        // Split fees according to the curve's snapshot or per-mint override (4-way split)
+        let mut bonding_curve = ctx.accounts.bonding_curve.load_mut()?;
+        let fee_override = active_fee_override(&bonding_curve, &ctx.accounts.fee_override)?;
+        let referral_fee_share = referral_fee_share(
+            &ctx.accounts.global,
+            &ctx.accounts.referral_vault
+        );
+        let (platform_fee, creator_fee, treasury_fee, early_bird_fee, referral_fee) =
+            bonding_curve.get_fee_splits(fee_amount, fee_override.as_ref(), referral_fee_share);
 
         // Update fee pools - these are tracked separately from reserves
-        ctx.accounts.bonding_curve.creator_fee_pool += creator_fee;
-        ctx.accounts.bonding_curve.treasury_fee_pool += treasury_fee;
-        ctx.accounts.bonding_curve.early_bird_pool += early_bird_fee;
-        ctx.accounts.bonding_curve.total_fees_accrued += creator_fee;
-        ctx.accounts.bonding_curve.total_treasury_fees_accrued += treasury_fee;
-        ctx.accounts.bonding_curve.total_early_bird_fees_accrued += early_bird_fee;
+        bonding_curve.accrue_fees(creator_fee, treasury_fee, early_bird_fee);
+        drop(bonding_curve);
+
+        // Transfer the referral cut to the referrer's vault
+        if let Some(vault) = ctx.accounts.referral_vault.as_mut() {
+            if referral_fee > 0 {
+                transfer(
+                    CpiContext::new(ctx.accounts.system_program.to_account_info(), Transfer {
+                        from: ctx.accounts.user.to_account_info(),
+                        to: vault.to_account_info(),
+                    }),
+                    referral_fee
+                )?;
+                vault.accrue(referral_fee);
+            }
+        }
 
         // Transfer platform fee directly to fee recipient
         transfer(
@@ -2006,7 +3999,10 @@ mod helpers {
         Ok(())
     }
 
-    pub fn mint_to_bonding_curve<'info>(ctx: &Context<Create>) -> Result<()> {
+    pub fn mint_to_bonding_curve<'info>(
+        ctx: &Context<Create>,
+        token_total_supply: u64
+    ) -> Result<()> {
         let authority_seed = &[b"mint-authority".as_ref(), &[ctx.bumps.mint_authority]];
         let seeds = [authority_seed.as_slice()];
 
@@ -2020,7 +4016,7 @@ mod helpers {
             &seeds
         );
 
-        token::mint_to(cpi_ctx, ctx.accounts.global.token_total_supply)
+        token::mint_to(cpi_ctx, token_total_supply)
     }
 
     pub fn set_metadata<'info>(
@@ -2083,9 +4079,19 @@ mod helpers {
 
 #[derive(Accounts)]
 pub struct ClaimCreatorFees<'info> {
+    #[account(seeds = [b"global"], bump)]
+    pub global: Account<'info, Global>,
+    #[account(seeds = [b"roles"], bump)]
+    pub roles: Account<'info, Roles>,
     pub mint: Account<'info, Mint>,
-    #[account(mut, seeds = [b"bonding-curve", mint.key().as_ref()], bump)]
-    pub bonding_curve: Account<'info, BondingCurve>,
+    #[account(
+        mut,
+        seeds = [b"bonding-curve", mint.key().as_ref()],
+        bump,
+        constraint = BondingCurve::is_migrated(&bonding_curve.to_account_info()) @
+            HorseFunError::CurveNeedsMigration
+    )]
+    pub bonding_curve: AccountLoader<'info, BondingCurve>,
     #[account(mut)]
     pub user: Signer<'info>,
     /// CHECK: We validate in custom logic
@@ -2096,22 +4102,25 @@ pub struct ClaimCreatorFees<'info> {
 impl<'info> ClaimCreatorFees<'info> {
     pub fn validate(&self) -> Result<()> {
         // First check if it's the withdraw authority (platform failsafe)
-        if self.user.key() == config_feature::withdraw_authority::ID {
-            msg!("Authorized: Withdraw authority");
+        if self.user.key() == self.roles.withdrawer {
+            verbose_msg!("Authorized: Withdraw authority");
             return Ok(());
         }
 
+        let bonding_curve = self.bonding_curve.load()?;
         // Check if token was created with a streamer ID
-        if let Some(token_streamer_id) = self.bonding_curve.creator_streamer_id.as_ref() {
-            msg!("Token has streamer ID: {}", token_streamer_id);
+        if let Some(token_streamer_id) = bonding_curve.creator_streamer_id() {
+            verbose_msg!("Token has streamer ID: {}", token_streamer_id);
 
             // When streamer_id exists, ONLY a verified streamer can claim
             let streamer_account_info = self.streamer_identity.as_ref().ok_or_else(|| {
-                msg!("Unauthorized: No streamer identity provided for token with streamer ID");
+                verbose_msg!(
+                    "Unauthorized: No streamer identity provided for token with streamer ID"
+                );
                 HorseFunError::UnauthorizedCreator
             })?;
 
-            msg!(
+            verbose_msg!(
                 "Attempting to deserialize streamer identity from account: {}",
                 streamer_account_info.key()
             );
@@ -2120,7 +4129,7 @@ impl<'info> ClaimCreatorFees<'info> {
             let streamer_identity = StreamerIdentity::try_deserialize(
                 &mut &streamer_account_info.try_borrow_data()?[..]
             ).map_err(|_| {
-                msg!("Failed to deserialize streamer identity account");
+                verbose_msg!("Failed to deserialize streamer identity account");
                 HorseFunError::UnauthorizedCreator
             })?;
 
@@ -2130,20 +4139,20 @@ impl<'info> ClaimCreatorFees<'info> {
                 streamer_identity.verified &&
                 streamer_identity.streamer_id == *token_streamer_id
             {
-                msg!("Authorized: Verified streamer identity");
+                verbose_msg!("Authorized: Verified streamer identity");
                 return Ok(());
             }
 
-            msg!("Unauthorized: Invalid or unverified streamer identity");
+            verbose_msg!("Unauthorized: Invalid or unverified streamer identity");
             return Err(HorseFunError::UnauthorizedCreator.into());
         } else {
-            msg!("Token has no streamer ID, checking creator wallet");
+            verbose_msg!("Token has no streamer ID, checking creator wallet");
             // No streamer ID, ONLY creator wallet can claim
             require!(
-                self.user.key() == self.bonding_curve.creator_wallet,
+                self.user.key() == bonding_curve.creator_wallet,
                 HorseFunError::UnauthorizedCreator
             );
-            msg!("Authorized: Original creator wallet");
+            verbose_msg!("Authorized: Original creator wallet");
             return Ok(());
         }
     }
@@ -2154,31 +4163,274 @@ pub struct StreamerIdentity {
     pub wallet: Pubkey,
     pub streamer_id: String,
     pub verified: bool,
+    pub version: u8,
+}
+
+/// Hidden trade for the commit-reveal flow; closed on reveal or cancel.
+#[account]
+pub struct TradeCommitment {
+    pub user: Pubkey,
+    pub mint: Pubkey,
+    pub commitment: [u8; 32], // keccak(side || amount || limit || salt)
+    pub committed_slot: u64,
+    pub escrowed_lamports: u64, // SOL held on this account, refunded on close
+    pub version: u8,
+}
+
+impl TradeCommitment {
+    pub const VERSION: u8 = 1;
+    pub const SIZE: usize = 8 + 32 + 32 + 32 + 8 + 8 + 1;
+}
+
+/// SOL escrow for a curve's opening auction; holds all commitments until settlement.
+#[account]
+pub struct AuctionEscrow {
+    pub mint: Pubkey,
+    pub version: u8,
+}
+
+impl AuctionEscrow {
+    pub const VERSION: u8 = 1;
+    pub const SIZE: usize = 8 + 32 + 1;
+}
+
+/// A bidder's commitment in an opening auction.
+#[account]
+pub struct AuctionBid {
+    pub user: Pubkey,
+    pub mint: Pubkey,
+    pub amount: u64, // SOL committed (fee included)
+    pub bid_index: u64, // Order of first commit, input to the seat shuffle
+    pub version: u8,
+}
+
+impl AuctionBid {
+    pub const VERSION: u8 = 1;
+    pub const SIZE: usize = 8 + 32 + 32 + 8 + 8 + 1;
+}
+
+pub const ROLE_COUNT: usize = 6;
+
+/// Operational roles held in the `Roles` account.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Role {
+    Admin,
+    Withdrawer,
+    IdentityVerifier,
+    CtoOperator,
+    Pauser,
+    FeeManager,
+}
+
+/// Operational keys, each rotated with a two-step propose/accept.
+#[account]
+pub struct Roles {
+    pub admin: Pubkey, // Proposes role changes
+    pub withdrawer: Pubkey, // withdraw and the creator fee / donation claim failsafe
+    pub identity_verifier: Pubkey, // Registers and cancels streamer identities
+    pub cto_operator: Pubkey, // Reassigns fee recipients on community takeovers
+    pub pauser: Pubkey, // Pauses the program and freezes curves
+    pub fee_manager: Pubkey, // Sets per-mint fee overrides
+    pub pending: [Pubkey; ROLE_COUNT], // Proposed key per Role (default = none)
+    pub version: u8,
+}
+
+impl Roles {
+    pub const VERSION: u8 = 1;
+    pub const SIZE: usize = 8 + 32 * 6 + 32 * ROLE_COUNT + 1;
+
+    pub fn key(&self, role: Role) -> Pubkey {
+        match role {
+            Role::Admin => self.admin,
+            Role::Withdrawer => self.withdrawer,
+            Role::IdentityVerifier => self.identity_verifier,
+            Role::CtoOperator => self.cto_operator,
+            Role::Pauser => self.pauser,
+            Role::FeeManager => self.fee_manager,
+        }
+    }
+
+    fn key_mut(&mut self, role: Role) -> &mut Pubkey {
+        match role {
+            Role::Admin => &mut self.admin,
+            Role::Withdrawer => &mut self.withdrawer,
+            Role::IdentityVerifier => &mut self.identity_verifier,
+            Role::CtoOperator => &mut self.cto_operator,
+            Role::Pauser => &mut self.pauser,
+            Role::FeeManager => &mut self.fee_manager,
+        }
+    }
+}
+
+/// Per-referrer escrow of referral fees; holds the lamports until claimed.
+#[account]
+pub struct ReferralVault {
+    pub referrer: Pubkey,
+    pub pending: u64, // Unclaimed referral fees (lamports)
+    pub total_earned: u64, // Total historical referral fees
+    pub version: u8,
+}
+
+impl ReferralVault {
+    pub const VERSION: u8 = 1;
+    pub const SIZE: usize = 8 + 32 + 8 + 8 + 1; // discriminator + referrer + pending + total_earned + version
+
+    pub fn accrue(&mut self, amount: u64) {
+        self.pending = self.pending.saturating_add(amount);
+        self.total_earned = self.total_earned.saturating_add(amount);
+    }
 }
 
 #[account]
 pub struct StreamerIdRegistry {
     pub streamer_id: String,
     pub wallet: Pubkey,
+    pub version: u8,
 }
 
 impl StreamerIdentity {
-    pub const SIZE: usize = 8 + 32 + 50 + 1; // 50 chars for streamer_id
+    pub const VERSION: u8 = 1;
+    // The original size left out the string prefix, so long ids had no room for `version`
+    pub const SIZE: usize = 8 + 32 + (4 + MAX_STREAMER_ID_LEN) + 1 + 1;
+
+    /// See `HolderStats::migrate`.
+    pub fn migrate(&mut self) -> (u8, u8) {
+        let from_version = self.version;
+        self.version = self.version.max(Self::VERSION);
+        (from_version, self.version)
+    }
+}
+
+impl StreamerIdRegistry {
+    pub const VERSION: u8 = 1;
+    // discriminator + streamer_id + wallet + version
+    pub const SIZE: usize = 8 + (4 + MAX_STREAMER_ID_LEN) + 32 + 1;
+
+    /// See `HolderStats::migrate`.
+    pub fn migrate(&mut self) -> (u8, u8) {
+        let from_version = self.version;
+        self.version = self.version.max(Self::VERSION);
+        (from_version, self.version)
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq)]
+pub struct BuybackParams {
+    pub backing_mult_bps: u16, // e.g. 11000 = 1.10× backing
+    pub ema_drop_bps: u16, // e.g. 9500  = 0.95× EMA
+    pub ema_alpha_bps: u16, // e.g. 2000  = 20% smoothing
+    pub spend_bps: u16, // e.g. 2000  = 20% treasury per trigger
+    pub max_supply_bps: u16, // e.g. 1000  = 10% on-curve cap
+    pub min_backing_lamports: u64, // floor to avoid dust-trigger
+    pub max_burn_percentage_bps: u16, // e.g. 2500 = 25% max total burn of total supply
+}
+
+impl BuybackParams {
+    /// `backing_mult_bps` is a multiplier and may exceed 10000; the rest are fractions.
+    pub fn validate(&self) -> Result<()> {
+        require!(
+            self.backing_mult_bps > 0 && self.ema_alpha_bps > 0,
+            HorseFunError::InvalidBuybackParams
+        );
+        require!(
+            self.ema_drop_bps <= 10_000 &&
+                self.ema_alpha_bps <= 10_000 &&
+                self.spend_bps <= 10_000 &&
+                self.max_supply_bps <= 10_000 &&
+                self.max_burn_percentage_bps <= 10_000,
+            HorseFunError::InvalidBuybackParams
+        );
+        Ok(())
+    }
+}
+
+/// `BuybackParams` as stored on the zero-copy `BondingCurve`: widest field first,
+/// explicit tail padding.
+#[zero_copy]
+#[derive(Debug, Default)]
+pub struct CurveBuybackParams {
+    pub min_backing_lamports: u64,
+    pub backing_mult_bps: u16,
+    pub ema_drop_bps: u16,
+    pub ema_alpha_bps: u16,
+    pub spend_bps: u16,
+    pub max_supply_bps: u16,
+    pub max_burn_percentage_bps: u16,
+    pub _padding: [u8; 4],
+}
+
+impl CurveBuybackParams {
+    pub const SIZE: usize = 8 + 2 + 2 + 2 + 2 + 2 + 2 + 4;
+}
+
+impl From<BuybackParams> for CurveBuybackParams {
+    fn from(params: BuybackParams) -> Self {
+        CurveBuybackParams {
+            min_backing_lamports: params.min_backing_lamports,
+            backing_mult_bps: params.backing_mult_bps,
+            ema_drop_bps: params.ema_drop_bps,
+            ema_alpha_bps: params.ema_alpha_bps,
+            spend_bps: params.spend_bps,
+            max_supply_bps: params.max_supply_bps,
+            max_burn_percentage_bps: params.max_burn_percentage_bps,
+            _padding: [0; 4],
+        }
+    }
+}
+
+impl From<CurveBuybackParams> for BuybackParams {
+    fn from(params: CurveBuybackParams) -> Self {
+        BuybackParams {
+            backing_mult_bps: params.backing_mult_bps,
+            ema_drop_bps: params.ema_drop_bps,
+            ema_alpha_bps: params.ema_alpha_bps,
+            spend_bps: params.spend_bps,
+            max_supply_bps: params.max_supply_bps,
+            min_backing_lamports: params.min_backing_lamports,
+            max_burn_percentage_bps: params.max_burn_percentage_bps,
+        }
+    }
+}
+
+/// Outcome of `BondingCurve::plan_buyback`: tokens to buy back and their SOL cost.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug)]
+pub struct BuybackPlan {
+    pub amount: u64,
+    pub cost: u64,
 }
 
-impl StreamerIdRegistry {
-    pub const SIZE: usize = 8 + 50 + 32; // discriminator + streamer_id + wallet
+/// Result of `quote_trade`, returned via `set_return_data`.
+/// `sol_amount` is the curve cost (buy) or gross output (sell) before fees;
+/// `net_sol_amount` is what the user pays (buy) or receives (sell).
+/// Reserve fields are the projected state after the trade and any buyback.
+/// `allowlist_required` means the buy also needs a presale proof (and its wallet cap).
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct TradeQuote {
+    pub is_buy: bool,
+    pub token_amount: u64,
+    pub sol_amount: u64,
+    pub fee: u64,
+    pub net_sol_amount: u64,
+    pub platform_fee: u64,
+    pub creator_fee: u64,
+    pub treasury_fee: u64,
+    pub early_bird_fee: u64,
+    pub referral_fee: u64,
+    pub buyback_triggered: bool,
+    pub buyback_cost: u64,
+    pub burn_amount: u64,
+    pub virtual_sol_reserves: u64,
+    pub virtual_token_reserves: u64,
+    pub real_sol_reserves: u64,
+    pub real_token_reserves: u64,
+    pub circulating_supply: u64,
+    pub treasury_fee_pool: u64,
+    pub complete: bool,
+    pub allowlist_required: bool,
 }
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug)]
-pub struct BuybackParams {
-    pub backing_mult_bps: u16, // e.g. 11000 = 1.10× backing
-    pub ema_drop_bps: u16, // e.g. 9500  = 0.95× EMA
-    pub ema_alpha_bps: u16, // e.g. 2000  = 20% smoothing
-    pub spend_bps: u16, // e.g. 2000  = 20% treasury per trigger
-    pub max_supply_bps: u16, // e.g. 1000  = 10% on-curve cap
-    pub min_backing_lamports: u64, // floor to avoid dust-trigger
-    pub max_burn_percentage_bps: u16, // e.g. 2500 = 25% max total burn of total supply
+impl TradeQuote {
+    pub const SIZE: usize = 1 + 9 * 8 + 1 + 8 * 8 + 1 + 1;
 }
 
 #[event]
@@ -2191,11 +4443,11 @@ pub struct StreamerIdentityRegisteredEvent {
 #[event_cpi]
 #[derive(Accounts)]
 pub struct ReassignFeeRecipient<'info> {
-    /// The platform authority that can reassign fee recipients
-    #[account(
-        mut,
-        constraint = platform_authority.key() == config_feature::platform_authority::ID
-    )]
+    #[account(seeds = [b"roles"], bump)]
+    pub roles: Account<'info, Roles>,
+
+    /// The CTO operator that can reassign fee recipients
+    #[account(mut, constraint = platform_authority.key() == roles.cto_operator)]
     pub platform_authority: Signer<'info>,
 
     /// The mint for the token
@@ -2205,9 +4457,11 @@ pub struct ReassignFeeRecipient<'info> {
     #[account(
         mut, 
         seeds = [b"bonding-curve", mint.key().as_ref()], 
-        bump
+        bump,
+        constraint = BondingCurve::is_migrated(&bonding_curve.to_account_info()) @
+            HorseFunError::CurveNeedsMigration
     )]
-    pub bonding_curve: Account<'info, BondingCurve>,
+    pub bonding_curve: AccountLoader<'info, BondingCurve>,
 
     pub system_program: Program<'info, System>,
 }
@@ -2215,11 +4469,14 @@ pub struct ReassignFeeRecipient<'info> {
 #[derive(Accounts)]
 #[instruction(streamer_id: String)]
 pub struct RegisterStreamerIdentity<'info> {
-    /// The platform authority that can register Streamer identities
-    #[account(
-        mut,
-        constraint = platform_authority.key() == config_feature::platform_authority::ID
-    )]
+    #[account(seeds = [b"global"], bump)]
+    pub global: Account<'info, Global>,
+
+    #[account(seeds = [b"roles"], bump)]
+    pub roles: Account<'info, Roles>,
+
+    /// The identity verifier that can register Streamer identities
+    #[account(mut, constraint = platform_authority.key() == roles.identity_verifier)]
     pub platform_authority: Signer<'info>,
 
     /// The user's wallet to link with Streamer
@@ -2252,11 +4509,11 @@ pub struct RegisterStreamerIdentity<'info> {
 #[derive(Accounts)]
 #[instruction(streamer_id: String)]
 pub struct CancelStreamerIdentity<'info> {
-    /// The platform authority that can cancel Streamer identities
-    #[account(
-        mut,
-        constraint = platform_authority.key() == config_feature::platform_authority::ID
-    )]
+    #[account(seeds = [b"roles"], bump)]
+    pub roles: Account<'info, Roles>,
+
+    /// The identity verifier that can cancel Streamer identities
+    #[account(mut, constraint = platform_authority.key() == roles.identity_verifier)]
     pub platform_authority: Signer<'info>,
 
     /// The user's wallet to unlink
@@ -2344,6 +4601,100 @@ pub enum HorseFunError {
     AlreadyClaimedEarlyBird,
     #[msg("Arithmetic overflow or underflow")]
     ArithmeticOverflow,
+    #[msg("slippage: Too few tokens received for the given amount of SOL.")]
+    TooFewTokensReceived,
+    #[msg("slippage: Too many tokens required to receive the given amount of SOL.")]
+    TooManyTokensRequired,
+    #[msg("The trade deadline or max slot has passed.")]
+    TradeExpired,
+    #[msg("Invalid, disabled or missing curve preset")]
+    InvalidCurvePreset,
+    #[msg("Fee override is outside the bounds set in Global")]
+    FeeOverrideOutOfBounds,
+    #[msg("This token has a fee override; pass its FeeOverride account")]
+    FeeOverrideRequired,
+    #[msg("A wallet cannot refer its own trades")]
+    SelfReferral,
+    #[msg("Fee tiers must be ascending with discounts of at most 10000 bps")]
+    InvalidFeeTiers,
+    #[msg("Buy exceeds the per-wallet token cap during the launch window")]
+    LaunchWalletCapExceeded,
+    #[msg("Buy exceeds the per-transaction SOL cap during the launch window")]
+    LaunchSolCapExceeded,
+    #[msg("Trading start must be in the future and earlier than the current schedule")]
+    InvalidTradingStart,
+    #[msg("Creator dev-buy requires associated_user, holder_stats and fee_recipient")]
+    MissingCreatorBuyAccounts,
+    #[msg("Presale must end after trading starts")]
+    InvalidPresale,
+    #[msg("Wallet is not on the presale allowlist")]
+    NotOnAllowlist,
+    #[msg("Buy exceeds the per-wallet presale cap")]
+    PresaleWalletCapExceeded,
+    #[msg("Opening auction must end in the future")]
+    InvalidAuctionWindow,
+    #[msg("Opening auction is not accepting commitments")]
+    AuctionNotActive,
+    #[msg("Opening auction has not been settled yet")]
+    AuctionNotEnded,
+    #[msg("Revealed trade does not match its commitment")]
+    InvalidTradeCommitment,
+    #[msg("Trade commitment cannot be revealed yet")]
+    RevealTooEarly,
+    #[msg("Price band must be at most 10000 bps")]
+    InvalidPriceBand,
+    #[msg("Trade moves the price beyond the intra-slot band")]
+    PriceBandExceeded,
+    #[msg("Trading has not started for this token yet")]
+    TradingNotStarted,
+    #[msg("This launch was cancelled by its creator")]
+    LaunchCancelled,
+    #[msg("Launch is already open or has traded")]
+    LaunchAlreadyOpen,
+    #[msg("Program is paused")]
+    ProgramPaused,
+    #[msg("Trading is frozen on this curve")]
+    CurveFrozen,
+    #[msg("No pending proposal for this role")]
+    NoPendingRole,
+    #[msg("Authority cannot be the default pubkey")]
+    InvalidAuthority,
+    #[msg("Authority transfer delay cannot be negative")]
+    InvalidAuthorityTransferDelay,
+    #[msg("No pending authority transfer")]
+    NoPendingAuthority,
+    #[msg("Authority transfer delay has not elapsed")]
+    AuthorityTransferTooEarly,
+    #[msg("Params are timelocked; use propose_params")]
+    ParamsTimelocked,
+    #[msg("Params timelock cannot be negative or shortened")]
+    InvalidParamsTimelock,
+    #[msg("Queued params are not executable yet")]
+    ParamsNotReady,
+    #[msg("Fee basis points exceed the allowed maximum")]
+    InvalidFeeBasisPoints,
+    #[msg("Buyback params out of range")]
+    InvalidBuybackParams,
+    #[msg("Early bird cutoff out of range")]
+    InvalidEarlyBirdConfig,
+    #[msg("Invalid default curve reserves or supply")]
+    InvalidCurveDefaults,
+    #[msg("The curve cannot fill this amount")]
+    CurveCannotFill,
+    #[msg("Launch protection window or caps out of range")]
+    InvalidLaunchProtection,
+    #[msg("Early bird cutoff cannot change once the curve has buyers")]
+    EarlyBirdCutoffLocked,
+    #[msg("Creator dev-buy is only allowed on launches that open immediately")]
+    CreatorBuyNotAllowed,
+    #[msg("An authority transfer is pending; cancel it first")]
+    AuthorityTransferPending,
+    #[msg("An opening auction can't be combined with a presale or launch protection")]
+    AuctionLaunchConflict,
+    #[msg("The opening auction's seed slot has not been produced yet")]
+    AuctionSeedPending,
+    #[msg("The bonding curve must be migrated with migrate_bonding_curve first")]
+    CurveNeedsMigration,
 }
 
 #[account]
@@ -2365,12 +4716,58 @@ pub struct Global {
     pub early_bird_enabled: bool, // Toggle for early bird rewards
     pub early_bird_cutoff: u64, // First X buyers get early bird rewards (e.g., 50)
     pub early_bird_min_buy_sol: u64, // Minimum SOL amount to qualify for early bird (in lamports)
+    pub max_override_fee_basis_points: u64, // Upper bound for per-mint FeeOverride fee_basis_points
+    pub min_override_platform_fee_share: u64, // Lower bound for per-mint FeeOverride platform_fee_share
+    pub referral_fee_share: u64, // Percentage of the platform fee paid to the referrer
+    pub fee_tiers: [FeeTier; MAX_FEE_TIERS], // Volume thresholds -> fee discount, ascending
+    pub launch_protection: LaunchProtection, // Default anti-sniper window for new curves
+    pub creator_buy_counts_as_early_bird: bool, // Whether a create dev-buy takes an early bird seat
+    pub commit_reveal_min_slots: u64, // Slots between commit_trade and its reveal
+    pub price_band_bps: u16, // Max intra-slot price move per curve (0 = breaker off)
+    pub paused: bool, // Halts every state-changing instruction, claims included
+    pub pending_authority: Pubkey, // Proposed successor to `authority` (default = none)
+    pub authority_proposed_at: i64, // When pending_authority was proposed
+    pub authority_transfer_delay: i64, // Min seconds between propose and accept
+    pub params_timelock_secs: i64, // Delay before queued params apply (0 = set_params allowed)
+    pub version: u8, // Layout version, bumped by migrate_global
 }
 
 impl Global {
-    // Updated size calculation: added early_bird_fee_share (8), early_bird_enabled (1), early_bird_cutoff (8), early_bird_min_buy_sol (8)
+    // Updated size calculation: added max_override_fee_basis_points (8), min_override_platform_fee_share (8), referral_fee_share (8), fee_tiers (4 * 10)
     pub const SIZE: usize =
-        8 + 32 + 1 + 32 + 8 + 8 + 8 + 8 + 8 + 8 + 8 + 8 + 8 + 8 + 1 + 18 + 1 + 8 + 8;
+        8 + 32 + 1 + 32 + 8 + 8 + 8 + 8 + 8 + 8 + 8 + 8 + 8 + 8 + 1 + 18 + 1 + 8 + 8 + 8 + 8 + 8 +
+        MAX_FEE_TIERS * FeeTier::SIZE +
+        LaunchProtection::SIZE +
+        1 +
+        8 +
+        2 +
+        1 +
+        32 +
+        8 +
+        8 +
+        8 +
+        1;
+
+    pub const VERSION: u8 = 1;
+
+    /// Default-initializes fields added since `self.version`. Safe to call repeatedly.
+    /// Fields appended after the original layout read back as zero after realloc, and
+    /// zero is already their intended default (features off, no pending transfers).
+    pub fn migrate(&mut self) {
+        if self.version < 1 {
+            self.version = 1;
+        }
+    }
+
+    /// Fee discount (bps of the fee) for a trader with `total_volume` on a mint.
+    pub fn fee_discount_bps(&self, total_volume: u64) -> u16 {
+        self.fee_tiers
+            .iter()
+            .filter(|tier| tier.min_volume > 0 && total_volume >= tier.min_volume)
+            .map(|tier| tier.discount_bps)
+            .max()
+            .unwrap_or(0)
+    }
 
     pub fn get_fee(&self, amount: u64) -> u64 {
         let fee = ((amount as u128) * (self.fee_basis_points as u128)) / 10_000;
@@ -2384,9 +4781,441 @@ impl Global {
         let early_bird_fee = ((total_fee as u128) * (self.early_bird_fee_share as u128)) / 10_000;
         (platform_fee as u64, creator_fee as u64, treasury_fee as u64, early_bird_fee as u64)
     }
+
+    /// Rejects direct parameter changes once a params timelock is configured.
+    pub fn check_params_unlocked(&self) -> Result<()> {
+        require!(self.params_timelock_secs == 0, HorseFunError::ParamsTimelocked);
+        Ok(())
+    }
+
+    /// Current values of everything `execute_params` can change.
+    pub fn params(&self) -> GlobalParams {
+        GlobalParams {
+            fee_recipient: self.fee_recipient,
+            initial_virtual_token_reserves: self.initial_virtual_token_reserves,
+            initial_virtual_sol_reserves: self.initial_virtual_sol_reserves,
+            initial_real_token_reserves: self.initial_real_token_reserves,
+            token_total_supply: self.token_total_supply,
+            fee_basis_points: self.fee_basis_points,
+            creator_fee_share: self.creator_fee_share,
+            platform_fee_share: self.platform_fee_share,
+            treasury_fee_share: self.treasury_fee_share,
+            early_bird_fee_share: self.early_bird_fee_share,
+            buybacks_enabled: self.buybacks_enabled,
+            buyback_params: self.buyback_params,
+            early_bird_enabled: self.early_bird_enabled,
+            early_bird_cutoff: self.early_bird_cutoff,
+            early_bird_min_buy_sol: self.early_bird_min_buy_sol,
+            max_override_fee_basis_points: self.max_override_fee_basis_points,
+            min_override_platform_fee_share: self.min_override_platform_fee_share,
+            referral_fee_share: self.referral_fee_share,
+            fee_tiers: self.fee_tiers,
+            launch_protection: self.launch_protection,
+            creator_buy_counts_as_early_bird: self.creator_buy_counts_as_early_bird,
+            commit_reveal_min_slots: self.commit_reveal_min_slots,
+            price_band_bps: self.price_band_bps,
+        }
+    }
+
+    pub fn apply_params(&mut self, params: &GlobalParams) {
+        self.fee_recipient = params.fee_recipient;
+        self.initial_virtual_token_reserves = params.initial_virtual_token_reserves;
+        self.initial_virtual_sol_reserves = params.initial_virtual_sol_reserves;
+        self.initial_real_token_reserves = params.initial_real_token_reserves;
+        self.token_total_supply = params.token_total_supply;
+        self.fee_basis_points = params.fee_basis_points;
+        self.creator_fee_share = params.creator_fee_share;
+        self.platform_fee_share = params.platform_fee_share;
+        self.treasury_fee_share = params.treasury_fee_share;
+        self.early_bird_fee_share = params.early_bird_fee_share;
+        self.buybacks_enabled = params.buybacks_enabled;
+        self.buyback_params = params.buyback_params;
+        self.early_bird_enabled = params.early_bird_enabled;
+        self.early_bird_cutoff = params.early_bird_cutoff;
+        self.early_bird_min_buy_sol = params.early_bird_min_buy_sol;
+        self.max_override_fee_basis_points = params.max_override_fee_basis_points;
+        self.min_override_platform_fee_share = params.min_override_platform_fee_share;
+        self.referral_fee_share = params.referral_fee_share;
+        self.fee_tiers = params.fee_tiers;
+        self.launch_protection = params.launch_protection;
+        self.creator_buy_counts_as_early_bird = params.creator_buy_counts_as_early_bird;
+        self.commit_reveal_min_slots = params.commit_reveal_min_slots;
+        self.price_band_bps = params.price_band_bps;
+    }
+
+    /// Launch parameters used by `create` when no curve preset is given.
+    pub fn default_curve_preset(&self) -> CurvePreset {
+        CurvePreset {
+            preset_id: 0,
+            name: String::from("default"),
+            enabled: true,
+            initial_virtual_token_reserves: self.initial_virtual_token_reserves,
+            initial_virtual_sol_reserves: self.initial_virtual_sol_reserves,
+            initial_real_token_reserves: self.initial_real_token_reserves,
+            token_total_supply: self.token_total_supply,
+            fee_basis_points: self.fee_basis_points,
+            early_bird_cutoff: self.early_bird_cutoff,
+            launch_protection: self.launch_protection,
+        }
+    }
+}
+
+/// The timelocked Global fields, queued as a unit by `propose_params`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug)]
+pub struct GlobalParams {
+    pub fee_recipient: Pubkey,
+    pub initial_virtual_token_reserves: u64,
+    pub initial_virtual_sol_reserves: u64,
+    pub initial_real_token_reserves: u64,
+    pub token_total_supply: u64,
+    pub fee_basis_points: u64,
+    pub creator_fee_share: u64,
+    pub platform_fee_share: u64,
+    pub treasury_fee_share: u64,
+    pub early_bird_fee_share: u64,
+    pub buybacks_enabled: bool,
+    pub buyback_params: BuybackParams,
+    pub early_bird_enabled: bool,
+    pub early_bird_cutoff: u64,
+    pub early_bird_min_buy_sol: u64,
+    pub max_override_fee_basis_points: u64,
+    pub min_override_platform_fee_share: u64,
+    pub referral_fee_share: u64,
+    pub fee_tiers: [FeeTier; MAX_FEE_TIERS],
+    pub launch_protection: LaunchProtection,
+    pub creator_buy_counts_as_early_bird: bool,
+    pub commit_reveal_min_slots: u64,
+    pub price_band_bps: u16,
+}
+
+impl GlobalParams {
+    pub const SIZE: usize =
+        32 + 8 * 9 + 1 + 20 + 1 + 8 * 5 +
+        MAX_FEE_TIERS * FeeTier::SIZE +
+        LaunchProtection::SIZE +
+        1 +
+        8 +
+        2;
+
+    pub fn validate(&self) -> Result<()> {
+        self.validate_fee_config()?;
+        self.buyback_params.validate()?;
+        self.validate_early_bird_config()?;
+        self.validate_curve_defaults()?;
+        Self::validate_fee_tiers(&self.fee_tiers)?;
+        self.validate_launch_protection()?;
+        self.validate_price_band()
+    }
+
+    pub fn validate_launch_protection(&self) -> Result<()> {
+        self.launch_protection.validate(self.initial_real_token_reserves)
+    }
+
+    /// Used rows in ascending `min_volume` order with non-decreasing discounts;
+    /// unused rows (all zero) may only follow them.
+    pub fn validate_fee_tiers(tiers: &[FeeTier]) -> Result<()> {
+        let used = tiers
+            .iter()
+            .take_while(|tier| tier.min_volume > 0)
+            .count();
+        require!(
+            tiers[used..].iter().all(|tier| tier.min_volume == 0 && tier.discount_bps == 0),
+            HorseFunError::InvalidFeeTiers
+        );
+        for (i, tier) in tiers[..used].iter().enumerate() {
+            require!(tier.discount_bps <= 10_000, HorseFunError::InvalidFeeTiers);
+            if i > 0 {
+                require!(
+                    tier.min_volume > tiers[i - 1].min_volume &&
+                        tier.discount_bps >= tiers[i - 1].discount_bps,
+                    HorseFunError::InvalidFeeTiers
+                );
+            }
+        }
+        Ok(())
+    }
+
+    pub fn validate_price_band(&self) -> Result<()> {
+        require!(self.price_band_bps <= 10_000, HorseFunError::InvalidPriceBand);
+        Ok(())
+    }
+
+    pub fn validate_fee_config(&self) -> Result<()> {
+        require!(
+            self.fee_basis_points <= MAX_FEE_BASIS_POINTS &&
+                self.max_override_fee_basis_points <= MAX_FEE_BASIS_POINTS,
+            HorseFunError::InvalidFeeBasisPoints
+        );
+        // Validate fee shares add up to 100%
+        helpers::check_fee_shares(
+            self.creator_fee_share,
+            self.platform_fee_share,
+            self.treasury_fee_share,
+            self.early_bird_fee_share
+        )?;
+        require!(
+            self.min_override_platform_fee_share <= 10_000 && self.referral_fee_share <= 10_000,
+            HorseFunError::InvalidFeeShares
+        );
+        Ok(())
+    }
+
+    pub fn validate_early_bird_config(&self) -> Result<()> {
+        require!(
+            !self.early_bird_enabled ||
+                (self.early_bird_cutoff > 0 && self.early_bird_cutoff <= MAX_EARLY_BIRD_CUTOFF),
+            HorseFunError::InvalidEarlyBirdConfig
+        );
+        Ok(())
+    }
+
+    pub fn validate_curve_defaults(&self) -> Result<()> {
+        require!(
+            self.initial_real_token_reserves > 0 &&
+                self.initial_real_token_reserves <= self.token_total_supply &&
+                self.initial_real_token_reserves < self.initial_virtual_token_reserves,
+            HorseFunError::InvalidCurveDefaults
+        );
+        require!(self.initial_virtual_sol_reserves > 0, HorseFunError::InvalidCurveDefaults);
+        Ok(())
+    }
+}
+
+/// A queued `GlobalParams` change, applied by `execute_params` after `eta`.
+#[account]
+pub struct PendingParams {
+    pub params: GlobalParams,
+    pub proposed_by: Pubkey,
+    pub proposed_at: i64,
+    pub eta: i64, // Earliest time execute_params succeeds
+    pub version: u8,
+}
+
+impl PendingParams {
+    pub const VERSION: u8 = 1;
+    pub const SIZE: usize = 8 + GlobalParams::SIZE + 32 + 8 + 8 + 1;
+}
+
+/// Anti-sniper limits applied to buys for `window_slots` slots after trading opens (0 = off).
+#[zero_copy]
+#[derive(AnchorSerialize, AnchorDeserialize, Debug, Default)]
+pub struct LaunchProtection {
+    pub window_slots: u64, // Protected slots from the first buy
+    pub max_tokens_per_wallet: u64, // Cap on a wallet's balance during the window (0 = no cap)
+    pub max_sol_per_tx: u64, // Cap on SOL cost per buy during the window (0 = no cap)
+}
+
+impl LaunchProtection {
+    pub const SIZE: usize = 8 + 8 + 8;
+
+    /// An active window must cap something, last at most `MAX_LAUNCH_WINDOW_SLOTS`
+    /// and not allow a single wallet more than the curve's `real_token_reserves`.
+    pub fn validate(&self, real_token_reserves: u64) -> Result<()> {
+        if self.window_slots == 0 {
+            return Ok(());
+        }
+        require!(
+            self.window_slots <= MAX_LAUNCH_WINDOW_SLOTS,
+            HorseFunError::InvalidLaunchProtection
+        );
+        require!(
+            self.max_tokens_per_wallet > 0 || self.max_sol_per_tx > 0,
+            HorseFunError::InvalidLaunchProtection
+        );
+        require!(
+            self.max_tokens_per_wallet <= real_token_reserves,
+            HorseFunError::InvalidLaunchProtection
+        );
+        Ok(())
+    }
+}
+
+/// Allowlisted pre-public phase; buys before `ends_at` need a merkle proof (0 = no presale).
+#[zero_copy]
+#[derive(AnchorSerialize, AnchorDeserialize, Debug, Default)]
+pub struct PresaleConfig {
+    pub merkle_root: [u8; 32], // Root over keccak(wallet) leaves
+    pub ends_at: i64, // Unix time the public phase begins
+    pub max_tokens_per_wallet: u64, // Cap on a wallet's balance during the presale (0 = no cap)
+}
+
+impl PresaleConfig {
+    pub const SIZE: usize = 32 + 8 + 8;
+}
+
+/// Opening batch auction state (`ends_at == 0` = no auction).
+#[zero_copy]
+#[derive(Debug, Default)]
+pub struct OpeningAuction {
+    pub ends_at: i64, // Commit window end; public trading waits for settlement
+    pub total_committed: u64, // SOL committed by all bidders (fee included)
+    pub bidder_count: u64,
+    pub tokens_cleared: u64, // Tokens bought for the whole pot at settlement
+    pub sol_cost: u64, // Curve cost of `tokens_cleared`
+    pub fee: u64, // Trading fee charged on `sol_cost`
+    pub seat_base: u64, // Early bird positions taken before the auction
+    pub seats: u64, // Early bird positions reserved for bidders
+    pub shuffle_a: u64, // Seat shuffle multiplier, coprime with bidder_count
+    pub shuffle_b: u64, // Seat shuffle offset
+    pub tokens_claimed: u64, // Tokens paid out so far; the last claimer takes the rounding dust
+    pub claimed_count: u64, // Bids claimed so far
+    pub seed_slot: u64, // Slot whose hash seeds the seat shuffle (0 = not picked yet)
+    pub settled: u8, // bool
+    pub seats_counted: u8, // bool; `seats` were added to early_bird_valid_count at settlement
+    pub _padding: [u8; 6],
+}
+
+impl OpeningAuction {
+    pub const SIZE: usize = 8 + 8 + 8 + 8 + 8 + 8 + 8 + 8 + 8 + 8 + 8 + 8 + 8 + 1 + 1 + 6;
+}
+
+/// Borsh layout of `OpeningAuction` before the zero-copy `BondingCurve`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default)]
+pub struct LegacyOpeningAuction {
+    pub ends_at: i64,
+    pub total_committed: u64,
+    pub bidder_count: u64,
+    pub settled: bool,
+    pub tokens_cleared: u64,
+    pub sol_cost: u64,
+    pub fee: u64,
+    pub seat_base: u64,
+    pub seats: u64,
+    pub shuffle_a: u64,
+    pub shuffle_b: u64,
+}
+
+impl LegacyOpeningAuction {
+    pub const SIZE: usize = 8 + 8 + 8 + 1 + 8 + 8 + 8 + 8 + 8 + 8 + 8;
+}
+
+impl From<LegacyOpeningAuction> for OpeningAuction {
+    /// Claims made before the migration are not tracked, so a settled legacy auction is
+    /// treated as fully claimed (no dust payout) and keeps counting seats at claim time.
+    fn from(legacy: LegacyOpeningAuction) -> Self {
+        let settled = legacy.settled;
+        OpeningAuction {
+            ends_at: legacy.ends_at,
+            total_committed: legacy.total_committed,
+            bidder_count: legacy.bidder_count,
+            tokens_cleared: legacy.tokens_cleared,
+            sol_cost: legacy.sol_cost,
+            fee: legacy.fee,
+            seat_base: legacy.seat_base,
+            seats: legacy.seats,
+            shuffle_a: legacy.shuffle_a,
+            shuffle_b: legacy.shuffle_b,
+            tokens_claimed: if settled { legacy.tokens_cleared } else { 0 },
+            claimed_count: if settled { legacy.bidder_count } else { 0 },
+            seed_slot: 0,
+            settled: settled as u8,
+            seats_counted: 0,
+            _padding: [0; 6],
+        }
+    }
+}
+
+pub const MAX_FEE_TIERS: usize = 4;
+
+/// Ceiling for Global trading fees and the FeeOverride bound (10%).
+pub const MAX_FEE_BASIS_POINTS: u64 = 1_000;
+
+/// Ceiling for the Global early bird seat count.
+pub const MAX_EARLY_BIRD_CUTOFF: u64 = 1_000;
+
+/// Slots between picking an opening auction's seed slot and that slot being produced.
+pub const AUCTION_SEED_DELAY_SLOTS: u64 = 8;
+
+/// Longest anti-sniper launch window (~1 hour of 400ms slots).
+pub const MAX_LAUNCH_WINDOW_SLOTS: u64 = 9_000;
+
+/// Longest streamer id, in bytes.
+pub const MAX_STREAMER_ID_LEN: usize = 50;
+
+/// Fixed-point scale for `BondingCurve::spot_price`.
+pub const PRICE_SCALE: u128 = 1_000_000_000_000;
+
+/// One row of the volume tier table; unused rows have `min_volume == 0`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default)]
+pub struct FeeTier {
+    pub min_volume: u64, // Lifetime SOL volume on the mint (lamports) to reach this tier
+    pub discount_bps: u16, // e.g. 2500 = 25% off fee_basis_points
+}
+
+impl FeeTier {
+    pub const SIZE: usize = 8 + 2;
+}
+
+#[account]
+pub struct CurvePreset {
+    pub preset_id: u16,
+    pub name: String, // e.g. "micro", "standard", "big streamer"
+    pub enabled: bool, // Disabled presets can no longer be used by create
+    pub initial_virtual_token_reserves: u64,
+    pub initial_virtual_sol_reserves: u64,
+    pub initial_real_token_reserves: u64,
+    pub token_total_supply: u64,
+    pub fee_basis_points: u64,
+    pub early_bird_cutoff: u64,
+    pub launch_protection: LaunchProtection,
+    pub version: u8,
+}
+
+impl CurvePreset {
+    pub const VERSION: u8 = 1;
+    pub const MAX_NAME_LEN: usize = 32;
+    pub const SIZE: usize =
+        8 + 2 + (4 + CurvePreset::MAX_NAME_LEN) + 1 + 8 + 8 + 8 + 8 + 8 + 8 +
+        LaunchProtection::SIZE +
+        1;
+
+    pub fn validate(&self) -> Result<()> {
+        require!(
+            !self.name.is_empty() && self.name.len() <= CurvePreset::MAX_NAME_LEN,
+            HorseFunError::InvalidCurvePreset
+        );
+        require!(
+            self.initial_real_token_reserves > 0 &&
+                self.initial_real_token_reserves <= self.token_total_supply &&
+                self.initial_real_token_reserves < self.initial_virtual_token_reserves,
+            HorseFunError::InvalidCurvePreset
+        );
+        require!(self.initial_virtual_sol_reserves > 0, HorseFunError::InvalidCurvePreset);
+        // Same bounds as the Global setters, so a preset cannot route around them
+        require!(
+            self.fee_basis_points <= MAX_FEE_BASIS_POINTS,
+            HorseFunError::InvalidFeeBasisPoints
+        );
+        require!(
+            self.early_bird_cutoff > 0 && self.early_bird_cutoff <= MAX_EARLY_BIRD_CUTOFF,
+            HorseFunError::InvalidEarlyBirdConfig
+        );
+        self.launch_protection.validate(self.initial_real_token_reserves)
+    }
 }
 
+/// Negotiated fee terms for a single mint, set by the fee manager role.
 #[account]
+pub struct FeeOverride {
+    pub mint: Pubkey,
+    pub enabled: bool,
+    pub fee_basis_points: u64,
+    pub creator_fee_share: u64,
+    pub platform_fee_share: u64,
+    pub treasury_fee_share: u64,
+    pub early_bird_fee_share: u64,
+    pub version: u8,
+}
+
+impl FeeOverride {
+    pub const VERSION: u8 = 1;
+    pub const SIZE: usize = 8 + 32 + 1 + 8 + 8 + 8 + 8 + 8 + 1;
+}
+
+/// Trading state of one mint, read in place on every trade instead of being
+/// deserialized. Fields are grouped by alignment so `repr(C)` adds no hidden padding;
+/// flags are `u8` (0 / 1) and the streamer id is a fixed-width, length-prefixed buffer.
+#[account(zero_copy)]
 pub struct BondingCurve {
     pub virtual_token_reserves: u64,
     pub virtual_sol_reserves: u64,
@@ -2394,6 +5223,76 @@ pub struct BondingCurve {
     pub real_sol_reserves: u64,
     pub token_total_supply: u64,
     pub circulating_supply: u64, // Track circulating supply separate from total supply
+
+    // Creator and treasury fees
+    pub creator_fee_pool: u64, // Creator's share of fees
+    pub treasury_fee_pool: u64, // Treasury's share of fees
+    pub total_fees_accrued: u64, // Total creator fees accrued
+    pub total_treasury_fees_accrued: u64, // Total treasury fees accrued
+    pub ema_lot_price: u64,
+
+    // Running totals for analytics
+    pub total_burned_supply: u64, // how much supply we have burned (ever)
+    pub total_treasury_spent: u64, // how many lamports spent on buybacks (ever)
+
+    // Early Bird Rewards
+    pub early_bird_pool: u64, // Accumulated SOL for early bird rewards
+    pub total_buyers: u64, // Total number of unique buyers (for position tracking)
+    pub total_early_bird_fees_accrued: u64, // Total historical early bird fees
+    pub early_bird_valid_count: u64, // Number of valid (non-revoked) early bird seats
+    pub early_bird_share_per_seat: u64, // Equal share amount calculated when curve completes (pool / valid_count)
+
+    // Curve shape
+    pub initial_virtual_token_reserves: u64, // T0: launch virtual token reserves (anchors non-CPMM shapes)
+    pub initial_virtual_sol_reserves: u64, // S0: launch virtual SOL reserves
+
+    // Launch preset snapshot
+    pub fee_basis_points: u64, // Trading fee for this curve
+    pub early_bird_cutoff: u64, // First X buyers get early bird rewards on this curve
+    pub creator_fee_share: u64, // Fee split snapshot, changed only by migrate_curve_params
+    pub platform_fee_share: u64,
+    pub treasury_fee_share: u64,
+    pub early_bird_fee_share: u64,
+    pub buyback_params: CurveBuybackParams, // Read through buyback_params()
+
+    // Launch schedule
+    pub created_slot: u64, // Slot the curve was created in
+    pub trading_opened_slot: u64, // First buy's slot, starts the launch window (0 = none yet)
+    pub launch_protection: LaunchProtection, // Anti-sniper limits snapshotted at create
+    pub trading_starts_at: i64, // Unix time trading opens (create time for immediate launches)
+    pub presale: PresaleConfig, // Allowlist phase for partnered launches
+    pub auction: OpeningAuction, // Optional batch auction that opens the curve
+
+    // Circuit breaker
+    pub price_ref_slot: u64, // Slot of the circuit breaker reference price
+    pub price_ref: [u8; 16], // u128 LE spot_price() at the start of price_ref_slot
+
+    pub creator_wallet: Pubkey,
+    pub preset_id: u16, // Curve preset used at create (0 = Global defaults)
+
+    // Flags
+    pub curve_kind: u8, // CurveKind discriminant, read through curve_kind()
+    pub complete: u8,
+    pub has_fee_override: u8, // Set while an enabled FeeOverride exists for this mint
+    pub launch_cancelled: u8, // Set when the creator cancels a scheduled launch
+    pub frozen: u8, // Trading-only freeze set by the pauser role; claims stay open
+    pub version: u8, // Layout version, bumped by migrate_bonding_curve
+
+    // Optional streamer ID for verification, read through creator_streamer_id()
+    pub creator_streamer_id_len: u8, // 0 = none
+    pub creator_streamer_id: [u8; MAX_STREAMER_ID_LEN],
+    pub _padding: [u8; 5],
+}
+
+/// Borsh layout of `BondingCurve` up to version 1, read once by `migrate_bonding_curve`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct LegacyBondingCurve {
+    pub virtual_token_reserves: u64,
+    pub virtual_sol_reserves: u64,
+    pub real_token_reserves: u64,
+    pub real_sol_reserves: u64,
+    pub token_total_supply: u64,
+    pub circulating_supply: u64, // Track circulating supply separate from total supply
     pub complete: bool,
     // Creator info and fees
     pub creator_wallet: Pubkey,
@@ -2408,14 +5307,182 @@ pub struct BondingCurve {
     pub total_burned_supply: u64, // how much supply we have burned (ever)
     pub total_treasury_spent: u64, // how many lamports spent on buybacks (ever)
 
-    // Early Bird Rewards
-    pub early_bird_pool: u64, // Accumulated SOL for early bird rewards
-    pub total_buyers: u64, // Total number of unique buyers (for position tracking)
-    pub total_early_bird_fees_accrued: u64, // Total historical early bird fees
-    pub early_bird_valid_count: u64, // Number of valid (non-revoked) early bird seats
-    pub early_bird_share_per_seat: u64, // Equal share amount calculated when curve completes (pool / valid_count)
+    // Early Bird Rewards
+    pub early_bird_pool: u64, // Accumulated SOL for early bird rewards
+    pub total_buyers: u64, // Total number of unique buyers (for position tracking)
+    pub total_early_bird_fees_accrued: u64, // Total historical early bird fees
+    pub early_bird_valid_count: u64, // Number of valid (non-revoked) early bird seats
+    pub early_bird_share_per_seat: u64, // Equal share amount calculated when curve completes (pool / valid_count)
+
+    // Curve shape
+    pub curve_kind: CurveKind, // Pricing formula chosen at create
+    pub initial_virtual_token_reserves: u64, // T0: launch virtual token reserves (anchors non-CPMM shapes)
+    pub initial_virtual_sol_reserves: u64, // S0: launch virtual SOL reserves
+
+    // Launch preset snapshot
+    pub preset_id: u16, // Curve preset used at create (0 = Global defaults)
+    pub fee_basis_points: u64, // Trading fee for this curve
+    pub early_bird_cutoff: u64, // First X buyers get early bird rewards on this curve
+    pub creator_fee_share: u64, // Fee split snapshot, changed only by migrate_curve_params
+    pub platform_fee_share: u64,
+    pub treasury_fee_share: u64,
+    pub early_bird_fee_share: u64,
+    pub buyback_params: BuybackParams,
+    pub has_fee_override: bool, // Set while an enabled FeeOverride exists for this mint
+    pub created_slot: u64, // Slot the curve was created in, start of the launch window
+    pub launch_protection: LaunchProtection, // Anti-sniper limits snapshotted at create
+    pub trading_starts_at: i64, // Unix time trading opens (create time for immediate launches)
+    pub launch_cancelled: bool, // Set when the creator cancels a scheduled launch
+    pub presale: PresaleConfig, // Allowlist phase for partnered launches
+    pub auction: LegacyOpeningAuction, // Optional batch auction that opens the curve
+    pub price_ref_slot: u64, // Slot of the circuit breaker reference price
+    pub price_ref: u128, // spot_price() at the start of price_ref_slot
+    pub frozen: bool, // Trading-only freeze set by the pauser role; claims stay open
+    pub version: u8,
+}
+
+impl LegacyBondingCurve {
+    pub const SIZE: usize =
+        8 + // discriminator
+        8 + // virtual_token_reserves
+        8 + // virtual_sol_reserves
+        8 + // real_token_reserves
+        8 + // real_sol_reserves
+        8 + // token_total_supply
+        8 + // circulating_supply
+        1 + // complete
+        32 + // creator_wallet
+        (4 + 50) + // creator_streamer_id (Option<String>)
+        8 + // creator_fee_pool
+        8 + // treasury_fee_pool
+        8 + // total_fees_accrued
+        8 + // total_treasury_fees_accrued
+        8 + // ema_lot_price
+        8 + // total_burned_supply
+        8 + // total_treasury_spent
+        8 + // early_bird_pool
+        8 + // total_buyers
+        8 + // total_early_bird_fees_accrued
+        8 + // early_bird_valid_count
+        8 + // early_bird_share_per_seat
+        1 + // curve_kind
+        8 + // initial_virtual_token_reserves
+        8 + // initial_virtual_sol_reserves
+        2 + // preset_id
+        8 + // fee_basis_points
+        8 + // early_bird_cutoff
+        8 + // creator_fee_share
+        8 + // platform_fee_share
+        8 + // treasury_fee_share
+        8 + // early_bird_fee_share
+        20 + // buyback_params
+        1 + // has_fee_override
+        8 + // created_slot
+        LaunchProtection::SIZE + // launch_protection
+        8 + // trading_starts_at
+        1 + // launch_cancelled
+        PresaleConfig::SIZE + // presale
+        LegacyOpeningAuction::SIZE + // auction
+        8 + // price_ref_slot
+        16 + // price_ref
+        1 + // frozen
+        1; // version
+
+    /// Default-initializes fields added since `self.version`. Safe to call repeatedly.
+    /// Curves created before the per-curve snapshot traded on Global's values, so an
+    /// empty snapshot is filled from `global`; everything else defaults to zero.
+    pub fn migrate(&mut self, global: &Global) {
+        if self.version < 1 {
+            let shares =
+                self.creator_fee_share +
+                self.platform_fee_share +
+                self.treasury_fee_share +
+                self.early_bird_fee_share;
+            if shares == 0 {
+                self.fee_basis_points = global.fee_basis_points;
+                self.early_bird_cutoff = global.early_bird_cutoff;
+                self.creator_fee_share = global.creator_fee_share;
+                self.platform_fee_share = global.platform_fee_share;
+                self.treasury_fee_share = global.treasury_fee_share;
+                self.early_bird_fee_share = global.early_bird_fee_share;
+                self.buyback_params = global.buyback_params;
+            }
+            if self.initial_virtual_token_reserves == 0 {
+                self.initial_virtual_token_reserves = global.initial_virtual_token_reserves;
+                self.initial_virtual_sol_reserves = global.initial_virtual_sol_reserves;
+            }
+            self.version = 1;
+        }
+    }
+
+    /// Reads a borsh-layout curve; the caller has already checked owner and discriminator.
+    /// Version 0 accounts are shorter than `SIZE`, so the missing tail is zero-extended
+    /// and later fields deserialize to their defaults.
+    pub fn read(info: &AccountInfo) -> Result<Self> {
+        let data = info.try_borrow_data()?;
+        let mut buf = data[8..].to_vec();
+        if buf.len() < LegacyBondingCurve::SIZE - 8 {
+            buf.resize(LegacyBondingCurve::SIZE - 8, 0);
+        }
+        Ok(LegacyBondingCurve::deserialize(&mut &buf[..])?)
+    }
+
+    /// Copies every field into a zeroed zero-copy curve and stamps the current version.
+    pub fn write_to(&self, curve: &mut BondingCurve) {
+        curve.virtual_token_reserves = self.virtual_token_reserves;
+        curve.virtual_sol_reserves = self.virtual_sol_reserves;
+        curve.real_token_reserves = self.real_token_reserves;
+        curve.real_sol_reserves = self.real_sol_reserves;
+        curve.token_total_supply = self.token_total_supply;
+        curve.circulating_supply = self.circulating_supply;
+        curve.creator_fee_pool = self.creator_fee_pool;
+        curve.treasury_fee_pool = self.treasury_fee_pool;
+        curve.total_fees_accrued = self.total_fees_accrued;
+        curve.total_treasury_fees_accrued = self.total_treasury_fees_accrued;
+        curve.ema_lot_price = self.ema_lot_price;
+        curve.total_burned_supply = self.total_burned_supply;
+        curve.total_treasury_spent = self.total_treasury_spent;
+        curve.early_bird_pool = self.early_bird_pool;
+        curve.total_buyers = self.total_buyers;
+        curve.total_early_bird_fees_accrued = self.total_early_bird_fees_accrued;
+        curve.early_bird_valid_count = self.early_bird_valid_count;
+        curve.early_bird_share_per_seat = self.early_bird_share_per_seat;
+        curve.initial_virtual_token_reserves = self.initial_virtual_token_reserves;
+        curve.initial_virtual_sol_reserves = self.initial_virtual_sol_reserves;
+        curve.fee_basis_points = self.fee_basis_points;
+        curve.early_bird_cutoff = self.early_bird_cutoff;
+        curve.creator_fee_share = self.creator_fee_share;
+        curve.platform_fee_share = self.platform_fee_share;
+        curve.treasury_fee_share = self.treasury_fee_share;
+        curve.early_bird_fee_share = self.early_bird_fee_share;
+        curve.buyback_params = self.buyback_params.into();
+        curve.created_slot = self.created_slot;
+        // The window used to run from create; curves nobody has bought yet start it on
+        // their first buy like new ones
+        if self.total_buyers > 0 {
+            curve.trading_opened_slot = self.created_slot;
+        }
+        curve.launch_protection = self.launch_protection;
+        curve.trading_starts_at = self.trading_starts_at;
+        curve.presale = self.presale;
+        curve.auction = self.auction.into();
+        curve.price_ref_slot = self.price_ref_slot;
+        curve.price_ref = self.price_ref.to_le_bytes();
+        curve.creator_wallet = self.creator_wallet;
+        curve.preset_id = self.preset_id;
+        curve.curve_kind = self.curve_kind as u8;
+        curve.complete = self.complete as u8;
+        curve.has_fee_override = self.has_fee_override as u8;
+        curve.launch_cancelled = self.launch_cancelled as u8;
+        curve.frozen = self.frozen as u8;
+        curve.set_creator_streamer_id(self.creator_streamer_id.as_deref());
+        curve.version = BondingCurve::VERSION;
+    }
 }
 
+// The migration tells layouts apart by account length
+const _: () = assert!(LegacyBondingCurve::SIZE < BondingCurve::SIZE);
+
 #[account]
 pub struct HolderStats {
     pub user: Pubkey,
@@ -2424,10 +5491,20 @@ pub struct HolderStats {
     pub fees_claimed: u64,
     pub entry_position: u64, // Position in line (1 = first buyer, 2 = second, etc.)
     pub total_volume: u64, // Lifetime trading volume for analytics
+    pub version: u8,
 }
 
 impl HolderStats {
-    pub const SIZE: usize = 8 + 32 + 32 + 8 + 8 + 8 + 8;
+    pub const VERSION: u8 = 1;
+    pub const SIZE: usize = 8 + 32 + 32 + 8 + 8 + 8 + 8 + 1;
+
+    /// Stamps `version` on stats created before the field existed; the byte reads back
+    /// as zero after `migrate_holder_stats` grows the account. Returns (from, to).
+    pub fn migrate(&mut self) -> (u8, u8) {
+        let from_version = self.version;
+        self.version = self.version.max(Self::VERSION);
+        (from_version, self.version)
+    }
 
     pub fn update_stats(&mut self, _clock: &Clock) -> Result<()> {
         Ok(())
@@ -2448,40 +5525,335 @@ pub struct MessageList {
     pub total_received: u64, // Total historical donations
     pub unclaimed_amount: u64, // Current unclaimed balance
     pub messages: Vec<Message>, // All messages received
+    pub version: u8,
 }
 
 impl MessageList {
+    pub const VERSION: u8 = 1;
     pub const SIZE: usize =
         8 + // Discriminator
         32 + // mint
         8 + // total_received
         8 + // unclaimed_amount
         4 + // Vec length
-        10 * (32 + 8 + (4 + 200) + 8); // 10 messages capacity
+        10 * (32 + 8 + (4 + 200) + 8) + // 10 messages capacity
+        1; // version
+
+    /// See `HolderStats::migrate`.
+    pub fn migrate(&mut self) -> (u8, u8) {
+        let from_version = self.version;
+        self.version = self.version.max(Self::VERSION);
+        (from_version, self.version)
+    }
 }
 
 impl BondingCurve {
+    /// Trading fee in bps, taking a per-mint `FeeOverride` over the curve snapshot,
+    /// then reduced by the trader's volume-tier discount.
+    fn fee_bps(&self, fee_override: Option<&FeeOverride>, discount_bps: u16) -> u64 {
+        let base = fee_override.map_or(self.fee_basis_points, |o| o.fee_basis_points);
+        let keep = 10_000u64.saturating_sub(discount_bps as u64);
+        (((base as u128) * (keep as u128)) / 10_000) as u64
+    }
+
+    pub fn get_fee(
+        &self,
+        amount: u64,
+        fee_override: Option<&FeeOverride>,
+        discount_bps: u16
+    ) -> u64 {
+        let fee =
+            ((amount as u128) * (self.fee_bps(fee_override, discount_bps) as u128)) / 10_000;
+        fee as u64
+    }
+
+    /// Splits a trade fee into (platform, creator, treasury, early_bird, referral).
+    /// The referral cut is carved out of the platform portion; pass 0 when there is no referrer.
+    pub fn get_fee_splits(
+        &self,
+        total_fee: u64,
+        fee_override: Option<&FeeOverride>,
+        referral_fee_share: u64
+    ) -> (u64, u64, u64, u64, u64) {
+        let (platform_share, creator_share, treasury_share, early_bird_share) = match fee_override {
+            Some(o) =>
+                (o.platform_fee_share, o.creator_fee_share, o.treasury_fee_share, o.early_bird_fee_share),
+            None =>
+                (
+                    self.platform_fee_share,
+                    self.creator_fee_share,
+                    self.treasury_fee_share,
+                    self.early_bird_fee_share,
+                ),
+        };
+        let platform_fee = ((total_fee as u128) * (platform_share as u128)) / 10_000;
+        let creator_fee = ((total_fee as u128) * (creator_share as u128)) / 10_000;
+        let treasury_fee = ((total_fee as u128) * (treasury_share as u128)) / 10_000;
+        let early_bird_fee = ((total_fee as u128) * (early_bird_share as u128)) / 10_000;
+        let referral_fee = (platform_fee * (referral_fee_share as u128)) / 10_000;
+        (
+            (platform_fee - referral_fee) as u64,
+            creator_fee as u64,
+            treasury_fee as u64,
+            early_bird_fee as u64,
+            referral_fee as u64,
+        )
+    }
+
+    /// Largest curve cost whose cost + fee still fits in `budget` (inverse of `get_fee`).
+    pub fn get_cost_for_budget(
+        &self,
+        budget: u64,
+        fee_override: Option<&FeeOverride>,
+        discount_bps: u16
+    ) -> u64 {
+        let fee_bps = self.fee_bps(fee_override, discount_bps);
+        let cost = ((budget as u128) * 10_000) / (10_000 + (fee_bps as u128));
+        cost as u64
+    }
+
+    /// Smallest gross output whose output - fee is at least `net` (None if fees take 100%).
+    pub fn get_output_for_net(
+        &self,
+        net: u64,
+        fee_override: Option<&FeeOverride>,
+        discount_bps: u16
+    ) -> Option<u64> {
+        let keep_bps = (10_000u128).checked_sub(self.fee_bps(fee_override, discount_bps) as u128)?;
+        if keep_bps == 0 {
+            return None;
+        }
+        let gross = ((net as u128) * 10_000 + keep_bps - 1) / keep_bps;
+        u64::try_from(gross).ok()
+    }
+
     /// Return the price to buy `amount` atomic units; safe guard.
     pub fn buy_quote_checked(&self, amount: u64) -> Option<u64> {
+        self.curve_kind().math().buy_quote_checked(self, amount)
+    }
+
+    /// Decide whether the EMA + backing buyback triggers and size it.
+    /// Updates the EMA as a side effect; reserves and pools are left untouched.
+    /// Returns None when no buyback should run.
+    pub fn plan_buyback(&mut self, params: &BuybackParams) -> Result<Option<BuybackPlan>> {
+        const DECIMALS: u32 = 6; // your mint decimals
+        const LOT_SIZE: u64 = (10u64).pow(DECIMALS); // 1 whole token
+
+        let backing_mult_bps = params.backing_mult_bps as u64;
+        let ema_drop_bps = params.ema_drop_bps as u64;
+        let ema_alpha_bps = params.ema_alpha_bps as u64;
+        let spend_bps = params.spend_bps as u64;
+        let max_supply_bps = params.max_supply_bps as u64;
+        let min_backing_lamports = params.min_backing_lamports; // ⭐ DON'T override this later!
+        let max_burn_pct = params.max_burn_percentage_bps as u64;
+
+        // Check if we've already hit the maximum burn limit (TOTAL cumulative burns, not per-tx)
+        let total_supply = self.token_total_supply;
+        let already_burned = self.total_burned_supply;
+        let current_burn_pct = if total_supply > 0 {
+            // Use u128 to prevent overflow on large token supplies
+            (((already_burned as u128) * 10_000) / (total_supply as u128)) as u64
+        } else {
+            0
+        };
+
+        if current_burn_pct >= max_burn_pct {
+            verbose_msg!(
+                "BB[LIMIT]: Max burn percentage reached: {}bps / {}bps ({}% / {}%)",
+                current_burn_pct,
+                max_burn_pct,
+                current_burn_pct / 100,
+                max_burn_pct / 100
+            );
+            verbose_msg!("BB[LIMIT]: Total burned: {} / {}", already_burned, total_supply);
+            return Ok(None);
+        }
+
+        verbose_msg!(
+            "BB[burn-status]: {}/{}bps used ({}% / {}%)",
+            current_burn_pct,
+            max_burn_pct,
+            current_burn_pct / 100,
+            max_burn_pct / 100
+        );
+
+        let vt = self.virtual_token_reserves;
+        let vsol = self.virtual_sol_reserves;
+        let rtok = self.real_token_reserves;
+        let tpool = self.treasury_fee_pool;
+
+        verbose_msg!("BB[state]: vt={} vsol={} rtok={} tpool={}", vt, vsol, rtok, tpool);
+
+        if vt <= 1 || tpool == 0 {
+            return Ok(None);
+        }
+
+        // 1) Quote a *lot*, not 1 atomic unit
+        let lot = LOT_SIZE.min(vt.saturating_sub(1));
+        let market_lot = match self.buy_quote_checked(lot) {
+            None => {
+                verbose_msg!("BB[skip]: unsafe lot quote (lot={} >= vT={})", lot, vt);
+                return Ok(None);
+            }
+            Some(market_lot) => market_lot,
+        };
+
+        // 2) Compute backing per lot with treasury
+        let backing_lot = self.backing_per_lot_with_treasury(lot, tpool);
+
+        // 3) Update EMA, then read it
+        self.update_ema_lot_price(market_lot, ema_alpha_bps);
+        let ema_lot = self.ema_lot_price;
+
+        // 4) Build trigger thresholds
+        let backing_thr = (((backing_lot as u128) * (backing_mult_bps as u128)) /
+            10_000u128) as u64;
+        let ema_thr = (((ema_lot as u128) * (ema_drop_bps as u128)) / 10_000u128) as u64;
+        let trigger_thr = backing_thr.max(ema_thr);
+
+        verbose_msg!(
+            "BB[chk]: lot={} mkt={} bkt={} ema={} thr={} tpool={}",
+            lot,
+            market_lot,
+            backing_lot,
+            ema_lot,
+            trigger_thr,
+            tpool
+        );
+
+        // 5) Trigger condition - balanced approach
+        // Allow buybacks when price is below threshold AND
+        // either backing is above a minimum OR the price drop is significant
+        let significant_drop = market_lot <= ema_lot / 2; // Price dropped by 50% or more from EMA
+
+        if
+            !(
+                market_lot <= trigger_thr &&
+                (backing_lot >= min_backing_lamports.into() || significant_drop)
+            )
+        {
+            verbose_msg!(
+                "BB[skip]: no trigger mkt={} thr={} bkt={}",
+                market_lot,
+                trigger_thr,
+                backing_lot
+            );
+            return Ok(None);
+        }
+
+        // Budget from treasury (lamports)
+        let budget = (((tpool as u128) * (spend_bps as u128)) / 10_000u128) as u64;
+        verbose_msg!("BB[budget]: spend_bps={} budget={}", spend_bps, budget);
+
+        // Convert budget → tokens with the curve shape's inversion
+        let mut amount = self.tokens_for_budget(budget);
+        verbose_msg!("BB[size0]: tokens_for_budget -> {}", amount);
+
+        // Cap to ≤10% of on-curve tokens; ensure it never floors to 0 when rtok>0
+        let mut max_supply_buy = if rtok == 0 {
+            0
+        } else {
+            (rtok.saturating_mul(max_supply_bps) / 10_000).max(1)
+        };
+        // Never exceed available curve inventory
+        if max_supply_buy > rtok {
+            max_supply_buy = rtok;
+        }
+
+        verbose_msg!(
+            "BB[caps]: rtok={} cap_bps={} cap_tokens={}",
+            rtok,
+            max_supply_bps,
+            max_supply_buy
+        );
+
+        if max_supply_buy > 0 {
+            amount = amount.min(max_supply_buy);
+            verbose_msg!("BB[size1]: after cap -> {}", amount);
+        } else {
+            verbose_msg!("BB[warn]: cap_tokens=0 (rtok=0), cannot buy");
+        }
+
+        // Guard rounding to zero → try minimum 1 atomic unit if affordable
         if amount == 0 {
-            return Some(0);
+            match self.buy_quote_checked(1) {
+                Some(min_cost) => {
+                    verbose_msg!("BB[fallback-atomic]: min_cost={} tpool={}", min_cost, tpool);
+                    if tpool >= min_cost && max_supply_buy >= 1 {
+                        amount = 1;
+                        verbose_msg!("BB[fallback-atomic]: amount set to 1");
+                    } else {
+                        verbose_msg!("BB[fallback-atomic]: insufficient tpool or cap<1");
+                    }
+                }
+                None => verbose_msg!("BB[fallback-atomic]: unsafe quote(1)"),
+            }
         }
-        let v_s = self.virtual_sol_reserves as u128;
-        let v_t = self.virtual_token_reserves as u128;
-        let a = amount as u128;
-        if a >= v_t {
-            return None;
-        } // would underflow
-        let num = a.checked_mul(v_s)?;
-        let den = v_t.checked_sub(a)?;
-        let q = num / den;
-        Some((q as u64).saturating_add(1))
+
+        // Optional second fallback: try one *lot* if affordable and within caps
+        if amount == 0 && lot > 0 && max_supply_buy >= lot {
+            match self.buy_quote_checked(lot) {
+                Some(lot_cost) => {
+                    verbose_msg!("BB[fallback-lot]: lot={} cost={} tpool={}", lot, lot_cost, tpool);
+                    if tpool >= lot_cost {
+                        amount = lot.min(max_supply_buy);
+                        verbose_msg!("BB[fallback-lot]: amount set to {}", amount);
+                    } else {
+                        verbose_msg!("BB[fallback-lot]: insufficient tpool for lot");
+                    }
+                }
+                None => verbose_msg!("BB[fallback-lot]: unsafe lot quote"),
+            }
+        }
+
+        if amount == 0 {
+            verbose_msg!("BB[skip]: amount=0 after sizing and fallbacks");
+            return Ok(None);
+        }
+
+        // Final quote & funds check
+        match self.buy_quote_checked(amount) {
+            None => {
+                verbose_msg!(
+                    "BB[skip]: unsafe quote for amount={} (vT={})",
+                    amount,
+                    self.virtual_token_reserves
+                );
+                Ok(None)
+            }
+            Some(bb_cost) => {
+                verbose_msg!("BB[quote]: amount={} bb_cost={} tpool={}", amount, bb_cost, tpool);
+                require!(tpool >= bb_cost, HorseFunError::InsufficientTreasuryFunds);
+                Ok(Some(BuybackPlan { amount, cost: bb_cost }))
+            }
+        }
+    }
+
+    /// Book-keep the fee splits that stay on the curve (creator / treasury / early bird).
+    pub fn accrue_fees(&mut self, creator_fee: u64, treasury_fee: u64, early_bird_fee: u64) {
+        self.creator_fee_pool += creator_fee;
+        self.treasury_fee_pool += treasury_fee;
+        self.early_bird_pool += early_bird_fee;
+        self.total_fees_accrued += creator_fee;
+        self.total_treasury_fees_accrued += treasury_fee;
+        self.total_early_bird_fees_accrued += early_bird_fee;
+    }
+
+    /// Book-keep a planned buyback: spend from the treasury pool and apply the
+    /// same reserve transition as a buy. Burn accounting is left to the caller.
+    pub fn apply_buyback(&mut self, plan: &BuybackPlan) {
+        self.treasury_fee_pool = self.treasury_fee_pool.saturating_sub(plan.cost);
+        self.virtual_token_reserves = self.virtual_token_reserves.saturating_sub(plan.amount);
+        self.virtual_sol_reserves = self.virtual_sol_reserves.saturating_add(plan.cost);
+        self.real_token_reserves = self.real_token_reserves.saturating_sub(plan.amount);
+        self.real_sol_reserves = self.real_sol_reserves.saturating_add(plan.cost);
+        self.total_treasury_spent = self.total_treasury_spent.saturating_add(plan.cost);
     }
 
     /// Backing per *lot* using treasury_pool and current circulating supply.
     /// lot is in atomic units (e.g., 1 token = 10^decimals).
     pub fn backing_per_lot_with_treasury(&self, lot: u64, treasury_pool: u64) -> u64 {
-        if self.complete || lot == 0 {
+        if self.complete != 0 || lot == 0 {
             return 0;
         }
         // Use circulating supply directly
@@ -2495,6 +5867,20 @@ impl BondingCurve {
         (tp.saturating_mul(l) / c) as u64
     }
 
+    /// Marginal price in lamports per token, scaled by `PRICE_SCALE`.
+    pub fn spot_price(&self) -> u128 {
+        self.curve_kind().math().spot_price(self)
+    }
+
+    /// Price at the start of `slot`; the first trade in a slot records it.
+    pub fn slot_reference_price(&mut self, slot: u64) -> u128 {
+        if self.price_ref_slot != slot {
+            self.price_ref_slot = slot;
+            self.price_ref = self.spot_price().to_le_bytes();
+        }
+        self.price_ref()
+    }
+
     /// Update EMA of lot price. Alpha in basis points (e.g., 2_000 for 20%).
     pub fn update_ema_lot_price(&mut self, new_price: u64, alpha_bps: u64) {
         // Initialize EMA on first update
@@ -2509,31 +5895,13 @@ impl BondingCurve {
         self.ema_lot_price = ((a * new + na * old) / 10_000) as u64;
     }
 
-    /// Invert the CPMM to size tokens for a SOL budget:
-    /// budget = Δt * vS / (vT - Δt)  => Δt = budget * vT / (vS + budget)
+    /// Invert the curve to size tokens for a SOL budget (strictly below vT).
     pub fn tokens_for_budget(&self, budget: u64) -> u64 {
-        if budget == 0 {
-            return 0;
-        }
-        let v_s = self.virtual_sol_reserves as u128;
-        let v_t = self.virtual_token_reserves as u128;
-        let b = budget as u128;
-        let den = v_s.saturating_add(b);
-        if den == 0 {
-            return 0;
-        }
-        let num = b.saturating_mul(v_t);
-        let mut dt = (num / den) as u64;
-        // must be strictly < v_t to avoid division by zero in quotes
-        let max_buy = self.virtual_token_reserves.saturating_sub(1);
-        if dt > max_buy {
-            dt = max_buy;
-        }
-        dt
+        self.curve_kind().math().tokens_for_budget(self, budget)
     }
 
     pub fn backing_per_token(&self) -> u64 {
-        if self.complete {
+        if self.complete != 0 {
             return 0; // curve finished, funds migrate
         }
 
@@ -2548,22 +5916,22 @@ impl BondingCurve {
         total_backing / circulating_supply
     }
 
+    /// Price to buy `amount` atomic units (always rounds up).
+    /// Saturates to u64::MAX when the curve cannot fill the amount, so slippage checks fail.
     pub fn buy_quote(&self, amount: u128) -> u64 {
-        let virtual_sol_reserves = self.virtual_sol_reserves as u128;
-        let virtual_token_reserves = self.virtual_token_reserves as u128;
-        let sol_cost: u64 = ((amount * virtual_sol_reserves) /
-            (virtual_token_reserves - amount)) as u64;
+        u64::try_from(amount)
+            .ok()
+            .and_then(|amount| self.buy_quote_checked(amount))
+            .unwrap_or(u64::MAX)
+    }
 
-        return sol_cost + 1; // always round up
+    /// Invert `sell_quote` to size the tokens needed for a SOL output.
+    pub fn tokens_for_sell_output(&self, sol_output: u64) -> Option<u64> {
+        self.curve_kind().math().tokens_for_sell_output(self, sol_output)
     }
 
     pub fn sell_quote(&self, amount: u128) -> u64 {
-        let virtual_sol_reserves = self.virtual_sol_reserves as u128;
-        let virtual_token_reserves = self.virtual_token_reserves as u128;
-        let sol_output: u64 = ((amount * virtual_sol_reserves) /
-            (virtual_token_reserves + amount)) as u64;
-
-        return sol_output;
+        self.curve_kind().math().sell_quote(self, amount as u64)
     }
 }
 
@@ -2576,9 +5944,6 @@ impl BondingCurve {
         8 + // real_sol_reserves
         8 + // token_total_supply
         8 + // circulating_supply
-        1 + // complete
-        32 + // creator_wallet
-        (4 + 50) + // creator_streamer_id (Option<String>)
         8 + // creator_fee_pool
         8 + // treasury_fee_pool
         8 + // total_fees_accrued
@@ -2590,7 +5955,83 @@ impl BondingCurve {
         8 + // total_buyers
         8 + // total_early_bird_fees_accrued
         8 + // early_bird_valid_count
-        8; // early_bird_share_per_seat
+        8 + // early_bird_share_per_seat
+        8 + // initial_virtual_token_reserves
+        8 + // initial_virtual_sol_reserves
+        8 + // fee_basis_points
+        8 + // early_bird_cutoff
+        8 + // creator_fee_share
+        8 + // platform_fee_share
+        8 + // treasury_fee_share
+        8 + // early_bird_fee_share
+        CurveBuybackParams::SIZE + // buyback_params
+        8 + // created_slot
+        8 + // trading_opened_slot
+        LaunchProtection::SIZE + // launch_protection
+        8 + // trading_starts_at
+        PresaleConfig::SIZE + // presale
+        OpeningAuction::SIZE + // auction
+        8 + // price_ref_slot
+        16 + // price_ref
+        32 + // creator_wallet
+        2 + // preset_id
+        1 + // curve_kind
+        1 + // complete
+        1 + // has_fee_override
+        1 + // launch_cancelled
+        1 + // frozen
+        1 + // version
+        1 + // creator_streamer_id_len
+        MAX_STREAMER_ID_LEN + // creator_streamer_id
+        5; // _padding
+
+    /// Version 2 is the zero-copy layout; 0 and 1 only exist as `LegacyBondingCurve`.
+    pub const VERSION: u8 = 2;
+
+    /// False for borsh-layout curves; loading one would read past the end of the account.
+    pub fn is_migrated(info: &AccountInfo) -> bool {
+        info.data_len() >= BondingCurve::SIZE
+    }
+
+    /// Brings an older zero-copy curve up to `VERSION`. Fields are only ever appended,
+    /// so the realloc in `migrate_bonding_curve` already zeroed them.
+    pub fn migrate(&mut self, _global: &Global) {
+        self.version = BondingCurve::VERSION;
+    }
+
+    pub fn curve_kind(&self) -> CurveKind {
+        CurveKind::from(self.curve_kind)
+    }
+
+    pub fn buyback_params(&self) -> BuybackParams {
+        self.buyback_params.into()
+    }
+
+    /// Slot the launch window runs from; `slot` when this trade is the one opening it.
+    pub fn launch_window_start(&self, slot: u64) -> u64 {
+        if self.trading_opened_slot == 0 { slot } else { self.trading_opened_slot }
+    }
+
+    pub fn price_ref(&self) -> u128 {
+        u128::from_le_bytes(self.price_ref)
+    }
+
+    pub fn creator_streamer_id(&self) -> Option<&str> {
+        let len = (self.creator_streamer_id_len as usize).min(MAX_STREAMER_ID_LEN);
+        if len == 0 {
+            return None;
+        }
+        std::str::from_utf8(&self.creator_streamer_id[..len]).ok()
+    }
+
+    /// Ids are validated to `MAX_STREAMER_ID_LEN` bytes by the callers; longer ones are cut.
+    pub fn set_creator_streamer_id(&mut self, streamer_id: Option<&str>) {
+        let bytes = streamer_id.map(str::as_bytes).unwrap_or_default();
+        let len = bytes.len().min(MAX_STREAMER_ID_LEN);
+        self.creator_streamer_id = [0; MAX_STREAMER_ID_LEN];
+        self.creator_streamer_id[..len].copy_from_slice(&bytes[..len]);
+        self.creator_streamer_id_len = len as u8;
+    }
 
     pub fn calculate_buyback_amount(&self) -> u64 {
         // Calculate market cap using virtual SOL reserves
@@ -2608,15 +6049,364 @@ impl BondingCurve {
             10
         };
 
-        // Calculate maximum SOL to use for buyback
-        let buyback_budget = (self.treasury_fee_pool * buyback_percentage) / 100;
+        // Calculate maximum SOL to use for buyback
+        let buyback_budget = (self.treasury_fee_pool * buyback_percentage) / 100;
+
+        // Calculate how many tokens we can buy with this budget
+        let token_amount = (((buyback_budget as u128) * (self.virtual_token_reserves as u128)) /
+            (self.virtual_sol_reserves as u128)) as u64;
+
+        token_amount
+    }
+}
+
+/// Price trajectory of a bonding curve, chosen at `create`.
+/// All shapes start at the same spot price (initial vSOL / initial vTOKEN).
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CurveKind {
+    /// Classic virtual-reserve CPMM: vSOL * vTOKEN = k
+    ConstantProduct,
+    /// Price grows linearly with tokens sold: p(s) = p0 * (1 + s / T0)
+    Linear,
+    /// Price grows exponentially with tokens sold: p(s) = p0 * e^(s / T0)
+    Exponential,
+}
+
+impl Default for CurveKind {
+    fn default() -> Self {
+        CurveKind::ConstantProduct
+    }
+}
+
+impl From<u8> for CurveKind {
+    /// Decodes `BondingCurve::curve_kind`; unknown values fall back to the default shape.
+    fn from(value: u8) -> Self {
+        match value {
+            1 => CurveKind::Linear,
+            2 => CurveKind::Exponential,
+            _ => CurveKind::ConstantProduct,
+        }
+    }
+}
+
+impl CurveKind {
+    pub fn math(&self) -> &'static dyn CurveMath {
+        match self {
+            CurveKind::ConstantProduct => &ConstantProductCurve,
+            CurveKind::Linear => &LinearCurve,
+            CurveKind::Exponential => &ExponentialCurve,
+        }
+    }
+}
+
+/// Quoting math for one curve shape. `BondingCurve` dispatches every quote,
+/// inversion and buyback sizing call through this trait.
+pub trait CurveMath {
+    /// Price to buy `amount` atomic units, rounded up; None if the curve cannot fill it.
+    fn buy_quote_checked(&self, curve: &BondingCurve, amount: u64) -> Option<u64>;
+    /// SOL returned (before fees) for selling `amount` atomic units, rounded down.
+    fn sell_quote(&self, curve: &BondingCurve, amount: u64) -> u64;
+    /// Tokens a SOL budget buys, always strictly below the virtual token reserves.
+    fn tokens_for_budget(&self, curve: &BondingCurve, budget: u64) -> u64;
+    /// Tokens to sell for at least `sol_output`; None if the curve cannot pay it.
+    fn tokens_for_sell_output(&self, curve: &BondingCurve, sol_output: u64) -> Option<u64>;
+    /// Marginal price in lamports per token, scaled by `PRICE_SCALE`.
+    fn spot_price(&self, curve: &BondingCurve) -> u128;
+}
+
+pub struct ConstantProductCurve;
+pub struct LinearCurve;
+pub struct ExponentialCurve;
+
+impl CurveMath for ConstantProductCurve {
+    fn buy_quote_checked(&self, curve: &BondingCurve, amount: u64) -> Option<u64> {
+        if amount == 0 {
+            return Some(0);
+        }
+        let v_s = curve.virtual_sol_reserves as u128;
+        let v_t = curve.virtual_token_reserves as u128;
+        let a = amount as u128;
+        if a >= v_t {
+            return None;
+        } // would underflow
+        let num = a.checked_mul(v_s)?;
+        let den = v_t.checked_sub(a)?;
+        let q = num / den;
+        Some((q as u64).saturating_add(1))
+    }
+
+    fn sell_quote(&self, curve: &BondingCurve, amount: u64) -> u64 {
+        let v_s = curve.virtual_sol_reserves as u128;
+        let v_t = curve.virtual_token_reserves as u128;
+        let a = amount as u128;
+        ((a * v_s) / (v_t + a)) as u64
+    }
+
+    /// Invert the CPMM to size tokens for a SOL budget:
+    /// budget = Δt * vS / (vT - Δt)  => Δt = budget * vT / (vS + budget)
+    fn tokens_for_budget(&self, curve: &BondingCurve, budget: u64) -> u64 {
+        if budget == 0 {
+            return 0;
+        }
+        let v_s = curve.virtual_sol_reserves as u128;
+        let v_t = curve.virtual_token_reserves as u128;
+        let b = budget as u128;
+        let den = v_s.saturating_add(b);
+        if den == 0 {
+            return 0;
+        }
+        let num = b.saturating_mul(v_t);
+        let dt = (num / den) as u64;
+        // must be strictly < v_t to avoid division by zero in quotes
+        dt.min(curve.virtual_token_reserves.saturating_sub(1))
+    }
+
+    /// Invert `sell_quote` to size the tokens needed for a SOL output:
+    /// out = Δt * vS / (vT + Δt)  => Δt = ceil(out * vT / (vS - out))
+    fn tokens_for_sell_output(&self, curve: &BondingCurve, sol_output: u64) -> Option<u64> {
+        let v_s = curve.virtual_sol_reserves as u128;
+        let v_t = curve.virtual_token_reserves as u128;
+        let out = sol_output as u128;
+        if out >= v_s {
+            return None;
+        }
+        let num = out.checked_mul(v_t)?;
+        let den = v_s - out;
+        u64::try_from((num + den - 1) / den).ok()
+    }
+
+    /// The reserve ratio vS / vT
+    fn spot_price(&self, curve: &BondingCurve) -> u128 {
+        if curve.virtual_token_reserves == 0 {
+            return 0;
+        }
+        ((curve.virtual_sol_reserves as u128) * PRICE_SCALE) /
+            (curve.virtual_token_reserves as u128)
+    }
+}
+
+impl LinearCurve {
+    /// SOL needed to sell `sold` tokens from launch:
+    /// C(s) = S0 * s * (2*T0 + s) / (2 * T0^2)
+    fn cost_to(curve: &BondingCurve, sold: u64) -> Option<u128> {
+        let t0 = curve.initial_virtual_token_reserves as u128;
+        let s0 = curve.initial_virtual_sol_reserves as u128;
+        if t0 == 0 {
+            return None;
+        }
+        let s = sold as u128;
+        // Divide by 2*T0 first so the S0 multiply stays inside u128
+        let q = s.checked_mul(2 * t0 + s)? / (2 * t0);
+        Some(q.checked_mul(s0)? / t0)
+    }
+
+    /// Largest `sold` with C(sold) <= cost: s = sqrt(T0^2 + 2 * T0^2 * cost / S0) - T0
+    fn sold_for_cost(curve: &BondingCurve, cost: u128) -> Option<u64> {
+        let t0 = curve.initial_virtual_token_reserves as u128;
+        let s0 = curve.initial_virtual_sol_reserves as u128;
+        if t0 == 0 || s0 == 0 {
+            return None;
+        }
+        let disc = t0
+            .checked_mul(t0)?
+            .checked_add(t0.checked_mul(cost)?.checked_div(s0)?.checked_mul(2 * t0)?)?;
+        u64::try_from(isqrt(disc).saturating_sub(t0)).ok()
+    }
+
+    fn sold(curve: &BondingCurve) -> u64 {
+        curve.initial_virtual_token_reserves.saturating_sub(curve.virtual_token_reserves)
+    }
+}
+
+impl CurveMath for LinearCurve {
+    fn buy_quote_checked(&self, curve: &BondingCurve, amount: u64) -> Option<u64> {
+        if amount == 0 {
+            return Some(0);
+        }
+        if amount >= curve.virtual_token_reserves {
+            return None;
+        }
+        let sold = Self::sold(curve);
+        let before = Self::cost_to(curve, sold)?;
+        let after = Self::cost_to(curve, sold.checked_add(amount)?)?;
+        let q = u64::try_from(after.saturating_sub(before)).ok()?;
+        Some(q.saturating_add(1))
+    }
+
+    fn sell_quote(&self, curve: &BondingCurve, amount: u64) -> u64 {
+        let sold = Self::sold(curve);
+        match (Self::cost_to(curve, sold), Self::cost_to(curve, sold.saturating_sub(amount))) {
+            (Some(before), Some(after)) => before.saturating_sub(after) as u64,
+            _ => 0,
+        }
+    }
+
+    fn tokens_for_budget(&self, curve: &BondingCurve, budget: u64) -> u64 {
+        if budget == 0 {
+            return 0;
+        }
+        let sold = Self::sold(curve);
+        let target = match Self::cost_to(curve, sold) {
+            Some(cost) => cost.saturating_add(budget as u128),
+            None => {
+                return 0;
+            }
+        };
+        let dt = Self::sold_for_cost(curve, target).unwrap_or(0).saturating_sub(sold);
+        dt.min(curve.virtual_token_reserves.saturating_sub(1))
+    }
+
+    fn tokens_for_sell_output(&self, curve: &BondingCurve, sol_output: u64) -> Option<u64> {
+        let sold = Self::sold(curve);
+        let target = Self::cost_to(curve, sold)?.checked_sub(sol_output as u128)?;
+        let mut dt = sold.checked_sub(Self::sold_for_cost(curve, target)?)?;
+        // isqrt floors; step once if rounding left the output short
+        if self.sell_quote(curve, dt) < sol_output {
+            dt = dt.checked_add(1)?;
+        }
+        if dt > sold {
+            return None;
+        }
+        Some(dt)
+    }
+
+    /// p(s) = S0 * (T0 + s) / T0^2
+    fn spot_price(&self, curve: &BondingCurve) -> u128 {
+        let t0 = curve.initial_virtual_token_reserves as u128;
+        if t0 == 0 {
+            return 0;
+        }
+        let scaled_s0 = (curve.initial_virtual_sol_reserves as u128).saturating_mul(PRICE_SCALE);
+        let span = t0 + (Self::sold(curve) as u128);
+        match scaled_s0.checked_mul(span) {
+            Some(num) => num / t0 / t0,
+            // Divide first on overflow, giving up some precision
+            None => ((scaled_s0 / t0).saturating_mul(span)) / t0,
+        }
+    }
+}
+
+impl CurveMath for ExponentialCurve {
+    /// cost = vS * (e^(Δt / T0) - 1)
+    fn buy_quote_checked(&self, curve: &BondingCurve, amount: u64) -> Option<u64> {
+        if amount == 0 {
+            return Some(0);
+        }
+        if amount >= curve.virtual_token_reserves || curve.initial_virtual_token_reserves == 0 {
+            return None;
+        }
+        let x = ((amount as u128) * WAD) / (curve.initial_virtual_token_reserves as u128);
+        let growth = exp_wad(x)?.checked_sub(WAD)?;
+        let q = (curve.virtual_sol_reserves as u128).checked_mul(growth)? / WAD;
+        Some(u64::try_from(q).ok()?.saturating_add(1))
+    }
+
+    /// output = vS * (1 - e^(-Δt / T0))
+    fn sell_quote(&self, curve: &BondingCurve, amount: u64) -> u64 {
+        if amount == 0 || curve.initial_virtual_token_reserves == 0 {
+            return 0;
+        }
+        let x = ((amount as u128) * WAD) / (curve.initial_virtual_token_reserves as u128);
+        let v_s = curve.virtual_sol_reserves as u128;
+        match exp_wad(x) {
+            Some(e) => (v_s - (v_s * WAD) / e) as u64,
+            None => curve.virtual_sol_reserves,
+        }
+    }
+
+    /// Δt = T0 * ln(1 + budget / vS)
+    fn tokens_for_budget(&self, curve: &BondingCurve, budget: u64) -> u64 {
+        if budget == 0 || curve.virtual_sol_reserves == 0 {
+            return 0;
+        }
+        let v_s = curve.virtual_sol_reserves as u128;
+        let ratio = ((v_s + (budget as u128)) * WAD) / v_s;
+        let dt = ((curve.initial_virtual_token_reserves as u128) * ln_wad(ratio)) / WAD;
+        (dt as u64).min(curve.virtual_token_reserves.saturating_sub(1))
+    }
+
+    /// Δt = T0 * ln(vS / (vS - out)), rounded up
+    fn tokens_for_sell_output(&self, curve: &BondingCurve, sol_output: u64) -> Option<u64> {
+        let v_s = curve.virtual_sol_reserves as u128;
+        let out = sol_output as u128;
+        if out >= v_s {
+            return None;
+        }
+        let ratio = v_s.checked_mul(WAD)? / (v_s - out);
+        let dt = (curve.initial_virtual_token_reserves as u128).checked_mul(ln_wad(ratio))? / WAD;
+        u64::try_from(dt + 1).ok()
+    }
+
+    /// d(cost)/dΔt at Δt = 0: vS / T0
+    fn spot_price(&self, curve: &BondingCurve) -> u128 {
+        if curve.initial_virtual_token_reserves == 0 {
+            return 0;
+        }
+        ((curve.virtual_sol_reserves as u128) * PRICE_SCALE) /
+            (curve.initial_virtual_token_reserves as u128)
+    }
+}
 
-        // Calculate how many tokens we can buy with this budget
-        let token_amount = (((buyback_budget as u128) * (self.virtual_token_reserves as u128)) /
-            (self.virtual_sol_reserves as u128)) as u64;
+/// Fixed-point scale used by the curve math (1.0 = 10^18).
+const WAD: u128 = 1_000_000_000_000_000_000;
+const LN2_WAD: u128 = 693_147_180_559_945_309;
 
-        token_amount
+/// e^x for x >= 0 in WAD fixed point (range-reduced by ln 2, then Taylor series).
+fn exp_wad(x: u128) -> Option<u128> {
+    let k = x / LN2_WAD;
+    if k >= 64 {
+        return None;
+    }
+    let r = x - k * LN2_WAD;
+    let mut sum = WAD;
+    let mut term = WAD;
+    for i in 1..=24u128 {
+        term = (term * r) / (i * WAD);
+        if term == 0 {
+            break;
+        }
+        sum += term;
+    }
+    sum.checked_mul(1u128 << k)
+}
+
+/// ln(z) for z >= 1 in WAD fixed point (range-reduced by powers of 2, then atanh series).
+fn ln_wad(z: u128) -> u128 {
+    if z <= WAD {
+        return 0;
     }
+    let mut m = z;
+    let mut k = 0u128;
+    while m >= 2 * WAD {
+        m >>= 1;
+        k += 1;
+    }
+    // ln(m) = 2 * atanh(t), t = (m - 1) / (m + 1) <= 1/3
+    let t = ((m - WAD) * WAD) / (m + WAD);
+    let t2 = (t * t) / WAD;
+    let mut sum = 0u128;
+    let mut term = t;
+    let mut n = 1u128;
+    while term > 0 && n < 64 {
+        sum += term / n;
+        term = (term * t2) / WAD;
+        n += 2;
+    }
+    2 * sum + k * LN2_WAD
+}
+
+/// Integer square root (floor) via Newton's method.
+fn isqrt(n: u128) -> u128 {
+    if n < 2 {
+        return n;
+    }
+    let mut x = n;
+    let mut y = (x + 1) / 2;
+    while y < x {
+        x = y;
+        y = (x + n / x) / 2;
+    }
+    x
 }
 
 #[event]
@@ -2627,6 +6417,11 @@ pub struct CreateEvent {
     pub mint: Pubkey,
     pub bonding_curve: Pubkey,
     pub user: Pubkey,
+    pub curve_kind: CurveKind,
+    pub preset_id: u16,
+    pub trading_starts_at: i64,
+    pub presale_ends_at: i64, // 0 when the launch has no allowlist phase
+    pub opening_auction_ends_at: i64, // 0 when the launch has no opening auction
 }
 
 #[event]
@@ -2665,6 +6460,11 @@ pub struct TradeEvent {
     total_buyers: u64, // Total unique buyers so far - helps backend detect missing events
     early_bird_valid_count: u64, // Number of non-revoked early bird seats - for consistency checks
     is_early_bird: bool,
+    // Referrals
+    referrer: Option<Pubkey>, // Referrer credited for this trade (if any)
+    referral_fee_amount: u64, // Referral fee carved from the platform fee of THIS trade
+    fee_discount_bps: u16, // Volume-tier discount applied to fee_basis_points for THIS trade
+    is_creator_buy: bool, // Creator dev-buy executed atomically inside create
 }
 
 #[event]
@@ -2717,6 +6517,7 @@ pub struct EarlyBirdClaimed {
 }
 
 #[event]
+#[derive(Clone)]
 pub struct SetParamsEvent {
     pub fee_recipient: Pubkey,
     pub initial_virtual_token_reserves: u64,
@@ -2727,13 +6528,410 @@ pub struct SetParamsEvent {
     pub creator_fee_share: u64,
     pub platform_fee_share: u64,
     pub treasury_fee_share: u64,
+    pub early_bird_fee_share: u64,
+    pub buybacks_enabled: bool,
+    pub buyback_params: BuybackParams,
+    pub early_bird_enabled: bool,
+    pub early_bird_cutoff: u64,
+    pub early_bird_min_buy_sol: u64,
+    pub max_override_fee_basis_points: u64,
+    pub min_override_platform_fee_share: u64,
+    pub referral_fee_share: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+#[derive(Clone)]
+pub struct FeeConfigSetEvent {
+    pub fee_recipient: Pubkey,
+    pub fee_basis_points: u64,
+    pub creator_fee_share: u64,
+    pub platform_fee_share: u64,
+    pub treasury_fee_share: u64,
+    pub early_bird_fee_share: u64,
+    pub max_override_fee_basis_points: u64,
+    pub min_override_platform_fee_share: u64,
+    pub referral_fee_share: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+#[derive(Clone)]
+pub struct BuybackParamsSetEvent {
     pub buybacks_enabled: bool,
+    pub buyback_params: BuybackParams,
+    pub timestamp: i64,
+}
+
+#[event]
+#[derive(Clone)]
+pub struct EarlyBirdConfigSetEvent {
+    pub early_bird_enabled: bool,
+    pub early_bird_cutoff: u64,
+    pub early_bird_min_buy_sol: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+#[derive(Clone)]
+pub struct CurveDefaultsSetEvent {
+    pub initial_virtual_token_reserves: u64,
+    pub initial_virtual_sol_reserves: u64,
+    pub initial_real_token_reserves: u64,
+    pub token_total_supply: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+#[derive(Clone)]
+pub struct CurvePresetSetEvent {
+    pub preset_id: u16,
+    pub name: String,
+    pub enabled: bool,
+    pub initial_virtual_token_reserves: u64,
+    pub initial_virtual_sol_reserves: u64,
+    pub initial_real_token_reserves: u64,
+    pub token_total_supply: u64,
+    pub fee_basis_points: u64,
+    pub early_bird_cutoff: u64,
+    pub launch_protection: LaunchProtection,
+}
+
+#[event]
+#[derive(Clone)]
+pub struct AccountMigratedEvent {
+    pub account: Pubkey,
+    pub from_version: u8,
+    pub to_version: u8,
+    pub timestamp: i64,
+}
+
+#[event]
+#[derive(Clone)]
+pub struct CurveParamsMigratedEvent {
+    pub mint: Pubkey,
+    pub old_fee_basis_points: u64,
+    pub old_creator_fee_share: u64,
+    pub old_platform_fee_share: u64,
+    pub old_treasury_fee_share: u64,
+    pub old_early_bird_fee_share: u64,
+    pub old_early_bird_cutoff: u64,
+    pub old_buyback_params: BuybackParams,
+    pub fee_basis_points: u64,
+    pub creator_fee_share: u64,
+    pub platform_fee_share: u64,
+    pub treasury_fee_share: u64,
+    pub early_bird_fee_share: u64,
+    pub early_bird_cutoff: u64,
+    pub buyback_params: BuybackParams,
+    pub timestamp: i64,
+}
+
+#[event]
+#[derive(Clone)]
+pub struct FeeOverrideSetEvent {
+    pub mint: Pubkey,
+    pub enabled: bool,
+    pub fee_basis_points: u64,
+    pub creator_fee_share: u64,
+    pub platform_fee_share: u64,
+    pub treasury_fee_share: u64,
+    pub early_bird_fee_share: u64,
+    pub platform_authority: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+#[derive(Clone)]
+pub struct ReferralFeesClaimedEvent {
+    pub referrer: Pubkey,
+    pub amount: u64,
+    pub total_earned: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+#[derive(Clone)]
+pub struct FeeTiersSetEvent {
+    pub tiers: Vec<FeeTier>,
+    pub timestamp: i64,
+}
+
+#[event]
+#[derive(Clone)]
+pub struct LaunchProtectionSetEvent {
+    pub launch_protection: LaunchProtection,
+    pub timestamp: i64,
+}
+
+#[event]
+#[derive(Clone)]
+pub struct TradingStartUpdatedEvent {
+    pub mint: Pubkey,
+    pub old_trading_starts_at: i64,
+    pub trading_starts_at: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+#[derive(Clone)]
+pub struct LaunchCancelledEvent {
+    pub mint: Pubkey,
+    pub creator: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+#[derive(Clone)]
+pub struct CreatorBuyConfigSetEvent {
+    pub counts_as_early_bird: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+#[derive(Clone)]
+pub struct AuctionCommitEvent {
+    pub mint: Pubkey,
+    pub user: Pubkey,
+    pub amount: u64,
+    pub user_total: u64,
+    pub total_committed: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+#[derive(Clone)]
+pub struct AuctionSeedSlotSetEvent {
+    pub mint: Pubkey,
+    pub seed_slot: u64, // settle_opening_auction can complete once this slot is produced
+    pub timestamp: i64,
+}
+
+#[event]
+#[derive(Clone)]
+pub struct AuctionSettledEvent {
+    pub mint: Pubkey,
+    pub total_committed: u64,
+    pub bidder_count: u64,
+    pub tokens_cleared: u64,
+    pub sol_cost: u64,
+    pub fee: u64,
+    pub seats: u64, // Early bird positions reserved for bidders
+    pub virtual_sol_reserves: u64,
+    pub virtual_token_reserves: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+#[derive(Clone)]
+pub struct AuctionClaimedEvent {
+    pub mint: Pubkey,
+    pub user: Pubkey,
+    pub committed: u64,
+    pub token_amount: u64,
+    pub sol_cost: u64,
+    pub refund: u64,
+    pub user_position: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+#[derive(Clone)]
+pub struct CommitRevealDelaySetEvent {
+    pub min_slots: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+#[derive(Clone)]
+pub struct PriceBandSetEvent {
+    pub price_band_bps: u16,
+    pub timestamp: i64,
+}
+
+#[event]
+#[derive(Clone)]
+pub struct ParamsTimelockSetEvent {
+    pub timelock_secs: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+#[derive(Clone)]
+pub struct ParamsProposedEvent {
+    pub current: GlobalParams,
+    pub proposed: GlobalParams,
+    pub eta: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+#[derive(Clone)]
+pub struct ParamsExecutedEvent {
+    pub previous: GlobalParams,
+    pub applied: GlobalParams,
+    pub timestamp: i64,
+}
+
+#[event]
+#[derive(Clone)]
+pub struct ParamsCancelledEvent {
+    pub current: GlobalParams,
+    pub cancelled: GlobalParams,
+    pub timestamp: i64,
+}
+
+#[event]
+#[derive(Clone)]
+pub struct AuthorityTransferDelaySetEvent {
+    pub delay_secs: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+#[derive(Clone)]
+pub struct AuthorityProposedEvent {
+    pub authority: Pubkey,
+    pub pending_authority: Pubkey,
+    pub accept_after: i64, // Earliest time accept_authority succeeds
+    pub timestamp: i64,
+}
+
+#[event]
+#[derive(Clone)]
+pub struct AuthorityTransferredEvent {
+    pub previous_authority: Pubkey,
+    pub new_authority: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+#[derive(Clone)]
+pub struct AuthorityTransferCancelledEvent {
+    pub authority: Pubkey,
+    pub cancelled_authority: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+#[derive(Clone)]
+pub struct RoleProposedEvent {
+    pub role: Role,
+    pub current: Pubkey,
+    pub proposed: Pubkey, // Pubkey::default() when a proposal is withdrawn
+    pub timestamp: i64,
+}
+
+#[event]
+#[derive(Clone)]
+pub struct RoleAcceptedEvent {
+    pub role: Role,
+    pub previous: Pubkey,
+    pub new_key: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+#[derive(Clone)]
+pub struct ProgramPausedSetEvent {
+    pub paused: bool,
+    pub pauser: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+#[derive(Clone)]
+pub struct CurveFrozenSetEvent {
+    pub mint: Pubkey,
+    pub frozen: bool,
+    pub pauser: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct CircuitBreakerTripped {
+    pub mint: Pubkey,
+    pub slot: u64,
+    pub reference_price: u128, // spot_price() at the start of the slot
+    pub attempted_price: u128, // spot_price() the trade would have left behind
+    pub deviation_bps: u64,
+    pub band_bps: u16,
+    pub timestamp: i64,
+}
+
+#[derive(Accounts)]
+pub struct Initialize<'info> {
+    #[account(init, payer = user, space = Global::SIZE, seeds = [b"global"], bump)]
+    pub global: Account<'info, Global>,
+    #[account(mut)]
+    pub user: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SetParams<'info> {
+    #[account(mut, seeds = [b"global"], bump)]
+    pub global: Account<'info, Global>,
+    #[account(mut)]
+    pub user: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ProposeParams<'info> {
+    #[account(seeds = [b"global"], bump)]
+    pub global: Account<'info, Global>,
+    #[account(
+        init,
+        payer = user,
+        space = PendingParams::SIZE,
+        seeds = [b"pending-params"],
+        bump
+    )]
+    pub pending_params: Account<'info, PendingParams>,
+    #[account(mut)]
+    pub user: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ResolveParams<'info> {
+    #[account(mut, seeds = [b"global"], bump)]
+    pub global: Account<'info, Global>,
+    #[account(mut, close = user, seeds = [b"pending-params"], bump)]
+    pub pending_params: Account<'info, PendingParams>,
+    #[account(mut)]
+    pub user: Signer<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(preset_id: u16)]
+pub struct SetCurvePreset<'info> {
+    #[account(seeds = [b"global"], bump)]
+    pub global: Account<'info, Global>,
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = CurvePreset::SIZE,
+        seeds = [b"curve-preset", preset_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub curve_preset: Account<'info, CurvePreset>,
+    #[account(mut)]
+    pub user: Signer<'info>,
+    pub system_program: Program<'info, System>,
 }
 
+#[event_cpi]
 #[derive(Accounts)]
-pub struct Initialize<'info> {
-    #[account(init, payer = user, space = Global::SIZE, seeds = [b"global"], bump)]
-    pub global: Account<'info, Global>,
+pub struct MigrateGlobal<'info> {
+    #[account(mut, seeds = [b"global"], bump, owner = crate::ID)]
+    /// CHECK: May predate the current layout; deserialized after realloc
+    pub global: UncheckedAccount<'info>,
     #[account(mut)]
     pub user: Signer<'info>,
     pub system_program: Program<'info, System>,
@@ -2741,14 +6939,75 @@ pub struct Initialize<'info> {
 
 #[event_cpi]
 #[derive(Accounts)]
-pub struct SetParams<'info> {
-    #[account(mut, seeds = [b"global"], bump)]
+pub struct MigrateAccount<'info> {
+    /// CHECK: May predate the current layout; the handler checks owner and discriminator
+    #[account(mut)]
+    pub account: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub user: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct MigrateBondingCurve<'info> {
+    #[account(seeds = [b"global"], bump)]
     pub global: Account<'info, Global>,
+    pub mint: Account<'info, Mint>,
+    /// Borsh and zero-copy curves share a discriminator; only loaded after realloc
+    #[account(mut, seeds = [b"bonding-curve", mint.key().as_ref()], bump)]
+    pub bonding_curve: AccountLoader<'info, BondingCurve>,
     #[account(mut)]
     pub user: Signer<'info>,
     pub system_program: Program<'info, System>,
 }
 
+#[event_cpi]
+#[derive(Accounts)]
+pub struct MigrateCurveParams<'info> {
+    #[account(seeds = [b"global"], bump)]
+    pub global: Account<'info, Global>,
+    pub mint: Account<'info, Mint>,
+    #[account(
+        mut,
+        seeds = [b"bonding-curve", mint.key().as_ref()],
+        bump,
+        constraint = BondingCurve::is_migrated(&bonding_curve.to_account_info()) @
+            HorseFunError::CurveNeedsMigration
+    )]
+    pub bonding_curve: AccountLoader<'info, BondingCurve>,
+    pub user: Signer<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SetFeeOverride<'info> {
+    #[account(seeds = [b"global"], bump)]
+    pub global: Account<'info, Global>,
+    #[account(seeds = [b"roles"], bump)]
+    pub roles: Account<'info, Roles>,
+    #[account(mut)]
+    pub platform_authority: Signer<'info>,
+    pub mint: Account<'info, Mint>,
+    #[account(
+        mut,
+        seeds = [b"bonding-curve", mint.key().as_ref()],
+        bump,
+        constraint = BondingCurve::is_migrated(&bonding_curve.to_account_info()) @
+            HorseFunError::CurveNeedsMigration
+    )]
+    pub bonding_curve: AccountLoader<'info, BondingCurve>,
+    #[account(
+        init_if_needed,
+        payer = platform_authority,
+        space = FeeOverride::SIZE,
+        seeds = [b"fee-override", mint.key().as_ref()],
+        bump
+    )]
+    pub fee_override: Account<'info, FeeOverride>,
+    pub system_program: Program<'info, System>,
+}
+
 #[event_cpi]
 #[derive(Accounts)]
 pub struct Create<'info> {
@@ -2764,181 +7023,561 @@ pub struct Create<'info> {
         seeds = [b"bonding-curve", mint.key().as_ref()],
         bump
     )]
-    pub bonding_curve: Box<Account<'info, BondingCurve>>,
+    pub bonding_curve: AccountLoader<'info, BondingCurve>,
+    #[account(
+        init,
+        payer = user,
+        associated_token::mint = mint,
+        associated_token::authority = bonding_curve
+    )]
+    pub associated_bonding_curve: Box<Account<'info, TokenAccount>>,
+    #[account(seeds = [b"global"], bump)]
+    pub global: Box<Account<'info, Global>>,
+    /// Optional launch preset; required when `preset_id` is given
+    pub curve_preset: Option<Box<Account<'info, CurvePreset>>>,
+    #[account(address = metadata::ID)]
+    /// CHECK: We already check the address matches the mpl_token_metadata program id.
+    pub mpl_token_metadata: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [b"metadata", metadata::ID.as_ref(),  mint.key().as_ref()],
+        bump,
+        seeds::program = metadata::ID
+    )]
+    /// CHECK: No need to check this
+    pub metadata: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub user: Signer<'info>,
+    /// Dev-buy only: the creator's token account
+    #[account(
+        init,
+        payer = user,
+        associated_token::mint = mint,
+        associated_token::authority = user
+    )]
+    pub associated_user: Option<Box<Account<'info, TokenAccount>>>,
+    /// Dev-buy only: the creator's holder stats
+    #[account(
+        init,
+        payer = user,
+        space = HolderStats::SIZE,
+        seeds = [b"holder-stats", mint.key().as_ref(), user.key().as_ref()],
+        bump
+    )]
+    pub holder_stats: Option<Box<Account<'info, HolderStats>>>,
+    /// Dev-buy only: platform fee destination
+    #[account(mut)]
+    /// CHECK: validated against global.fee_recipient
+    pub fee_recipient: Option<UncheckedAccount<'info>>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct Buy<'info> {
+    #[account(seeds = [b"global"], bump)]
+    pub global: Account<'info, Global>,
+    #[account(mut)]
+    /// CHECK: destination address
+    pub fee_recipient: UncheckedAccount<'info>,
+    #[account(mut)] // <-- make writable for burn
+    pub mint: Account<'info, Mint>,
+    #[account(
+        mut,
+        seeds = [b"bonding-curve", mint.key().as_ref()],
+        bump,
+        constraint = BondingCurve::is_migrated(&bonding_curve.to_account_info()) @
+            HorseFunError::CurveNeedsMigration
+    )]
+    pub bonding_curve: AccountLoader<'info, BondingCurve>,
+    #[account(
+        mut,
+        seeds = [bonding_curve.key().as_ref(), token::ID.as_ref(), mint.key().as_ref()],
+        bump,
+        seeds::program = associated_token::ID
+    )]
+    pub associated_bonding_curve: Account<'info, TokenAccount>,
+    #[account(mut)]
+    pub associated_user: Account<'info, TokenAccount>,
+    #[account(mut)]
+    pub user: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"holder-stats", mint.key().as_ref(), user.key().as_ref()],
+        bump
+    )]
+    pub holder_stats: Account<'info, HolderStats>,
+    #[account(seeds = [b"fee-override", mint.key().as_ref()], bump)]
+    pub fee_override: Option<Account<'info, FeeOverride>>,
+    /// Optional referrer, credited with `referral_fee_share` of the platform fee
+    #[account(
+        mut,
+        seeds = [b"referral-vault", referral_vault.referrer.as_ref()],
+        bump,
+        constraint = referral_vault.referrer != user.key() @ HorseFunError::SelfReferral
+    )]
+    pub referral_vault: Option<Account<'info, ReferralVault>>,
+    /// Commit-reveal only: the commitment being revealed
+    #[account(
+        mut,
+        seeds = [b"trade-commitment", mint.key().as_ref(), user.key().as_ref()],
+        bump
+    )]
+    pub trade_commitment: Option<Account<'info, TradeCommitment>>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct Sell<'info> {
+    #[account(seeds = [b"global"], bump)]
+    pub global: Account<'info, Global>,
+    #[account(mut)]
+    /// CHECK: destination address
+    pub fee_recipient: UncheckedAccount<'info>,
+    #[account(mut)] // <-- make writable for burn
+    pub mint: Account<'info, Mint>,
+    #[account(
+        mut,
+        seeds = [b"bonding-curve", mint.key().as_ref()],
+        bump,
+        constraint = BondingCurve::is_migrated(&bonding_curve.to_account_info()) @
+            HorseFunError::CurveNeedsMigration
+    )]
+    pub bonding_curve: AccountLoader<'info, BondingCurve>,
+    #[account(
+        mut,
+        seeds = [bonding_curve.key().as_ref(), token::ID.as_ref(), mint.key().as_ref()],
+        bump,
+        seeds::program = associated_token::ID
+    )]
+    pub associated_bonding_curve: Account<'info, TokenAccount>,
+    #[account(mut)]
+    pub associated_user: Account<'info, TokenAccount>,
+    #[account(mut)]
+    pub user: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"holder-stats", mint.key().as_ref(), user.key().as_ref()],
+        bump
+    )]
+    pub holder_stats: Account<'info, HolderStats>,
+    #[account(seeds = [b"fee-override", mint.key().as_ref()], bump)]
+    pub fee_override: Option<Account<'info, FeeOverride>>,
+    /// Optional referrer, credited with `referral_fee_share` of the platform fee
+    #[account(
+        mut,
+        seeds = [b"referral-vault", referral_vault.referrer.as_ref()],
+        bump,
+        constraint = referral_vault.referrer != user.key() @ HorseFunError::SelfReferral
+    )]
+    pub referral_vault: Option<Account<'info, ReferralVault>>,
+    /// Commit-reveal only: the commitment being revealed
+    #[account(
+        mut,
+        seeds = [b"trade-commitment", mint.key().as_ref(), user.key().as_ref()],
+        bump
+    )]
+    pub trade_commitment: Option<Account<'info, TradeCommitment>>,
+    pub system_program: Program<'info, System>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct CommitTrade<'info> {
+    #[account(seeds = [b"global"], bump)]
+    pub global: Account<'info, Global>,
+    pub mint: Account<'info, Mint>,
+    #[account(
+        seeds = [b"bonding-curve", mint.key().as_ref()],
+        bump,
+        constraint = BondingCurve::is_migrated(&bonding_curve.to_account_info()) @
+            HorseFunError::CurveNeedsMigration
+    )]
+    pub bonding_curve: AccountLoader<'info, BondingCurve>,
+    #[account(
+        init,
+        payer = user,
+        space = TradeCommitment::SIZE,
+        seeds = [b"trade-commitment", mint.key().as_ref(), user.key().as_ref()],
+        bump
+    )]
+    pub trade_commitment: Account<'info, TradeCommitment>,
+    #[account(mut)]
+    pub user: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CancelTradeCommitment<'info> {
+    pub mint: Account<'info, Mint>,
+    #[account(
+        mut,
+        close = user,
+        seeds = [b"trade-commitment", mint.key().as_ref(), user.key().as_ref()],
+        bump,
+        has_one = user
+    )]
+    pub trade_commitment: Account<'info, TradeCommitment>,
+    #[account(mut)]
+    pub user: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct QuoteTrade<'info> {
+    #[account(seeds = [b"global"], bump)]
+    pub global: Account<'info, Global>,
+    pub mint: Account<'info, Mint>,
+    #[account(
+        seeds = [b"bonding-curve", mint.key().as_ref()],
+        bump,
+        constraint = BondingCurve::is_migrated(&bonding_curve.to_account_info()) @
+            HorseFunError::CurveNeedsMigration
+    )]
+    pub bonding_curve: AccountLoader<'info, BondingCurve>,
+    #[account(seeds = [b"fee-override", mint.key().as_ref()], bump)]
+    pub fee_override: Option<Account<'info, FeeOverride>>,
+    /// Optional trader stats; applies their volume-tier fee discount and launch caps
+    #[account(constraint = holder_stats.mint == mint.key())]
+    pub holder_stats: Option<Account<'info, HolderStats>>,
+    /// Optional referrer, so the platform/referral split matches a referred trade
+    #[account(seeds = [b"referral-vault", referral_vault.referrer.as_ref()], bump)]
+    pub referral_vault: Option<Account<'info, ReferralVault>>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct InitHolderStats<'info> {
+    pub mint: Account<'info, Mint>,
+    #[account(
+        init,
+        payer = user,
+        space = HolderStats::SIZE,
+        seeds = [b"holder-stats", mint.key().as_ref(), user.key().as_ref()],
+        bump
+    )]
+    pub holder_stats: Account<'info, HolderStats>,
+    pub associated_user: Account<'info, TokenAccount>,
+    #[account(mut)]
+    pub user: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateHolderStats<'info> {
+    pub mint: Account<'info, Mint>,
+    #[account(mut)]
+    pub associated_user: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        seeds = [b"holder-stats", mint.key().as_ref(), user.key().as_ref()],
+        bump
+    )]
+    pub holder_stats: Account<'info, HolderStats>,
+    #[account(mut)]
+    pub user: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimHolderFees<'info> {
+    pub mint: Account<'info, Mint>,
     #[account(
-        init,
-        payer = user,
-        associated_token::mint = mint,
-        associated_token::authority = bonding_curve
+        mut,
+        seeds = [b"bonding-curve", mint.key().as_ref()],
+        bump,
+        constraint = BondingCurve::is_migrated(&bonding_curve.to_account_info()) @
+            HorseFunError::CurveNeedsMigration
     )]
-    pub associated_bonding_curve: Box<Account<'info, TokenAccount>>,
-    #[account(seeds = [b"global"], bump)]
-    pub global: Box<Account<'info, Global>>,
-    #[account(address = metadata::ID)]
-    /// CHECK: We already check the address matches the mpl_token_metadata program id.
-    pub mpl_token_metadata: UncheckedAccount<'info>,
+    pub bonding_curve: AccountLoader<'info, BondingCurve>,
     #[account(
         mut,
-        seeds = [b"metadata", metadata::ID.as_ref(),  mint.key().as_ref()],
-        bump,
-        seeds::program = metadata::ID
+        seeds = [b"holder-stats", mint.key().as_ref(), user.key().as_ref()],
+        bump
     )]
-    /// CHECK: No need to check this
-    pub metadata: UncheckedAccount<'info>,
+    pub holder_stats: Account<'info, HolderStats>,
+    #[account(mut)]
+    pub associated_user: Account<'info, TokenAccount>,
     #[account(mut)]
     pub user: Signer<'info>,
     pub system_program: Program<'info, System>,
-    pub token_program: Program<'info, Token>,
-    pub associated_token_program: Program<'info, AssociatedToken>,
-    pub rent: Sysvar<'info, Rent>,
 }
 
 #[event_cpi]
 #[derive(Accounts)]
-pub struct Buy<'info> {
+pub struct ClaimEarlyBirdRewards<'info> {
     #[account(seeds = [b"global"], bump)]
     pub global: Account<'info, Global>,
-    #[account(mut)]
-    /// CHECK: destination address
-    pub fee_recipient: UncheckedAccount<'info>,
-    #[account(mut)] // <-- make writable for burn
     pub mint: Account<'info, Mint>,
-    #[account(mut, seeds = [b"bonding-curve", mint.key().as_ref()], bump)]
-    pub bonding_curve: Account<'info, BondingCurve>,
     #[account(
         mut,
-        seeds = [bonding_curve.key().as_ref(), token::ID.as_ref(), mint.key().as_ref()],
+        seeds = [b"bonding-curve", mint.key().as_ref()],
         bump,
-        seeds::program = associated_token::ID
+        constraint = BondingCurve::is_migrated(&bonding_curve.to_account_info()) @
+            HorseFunError::CurveNeedsMigration
     )]
-    pub associated_bonding_curve: Account<'info, TokenAccount>,
-    #[account(mut)]
-    pub associated_user: Account<'info, TokenAccount>,
-    #[account(mut)]
-    pub user: Signer<'info>,
+    pub bonding_curve: AccountLoader<'info, BondingCurve>,
     #[account(
         mut,
         seeds = [b"holder-stats", mint.key().as_ref(), user.key().as_ref()],
         bump
     )]
     pub holder_stats: Account<'info, HolderStats>,
+    #[account(mut)]
+    pub user: Signer<'info>,
     pub system_program: Program<'info, System>,
-    pub token_program: Program<'info, Token>,
-    pub rent: Sysvar<'info, Rent>,
 }
 
-#[event_cpi]
 #[derive(Accounts)]
-pub struct Sell<'info> {
+pub struct InitReferralVault<'info> {
     #[account(seeds = [b"global"], bump)]
     pub global: Account<'info, Global>,
+    #[account(
+        init,
+        payer = referrer,
+        space = ReferralVault::SIZE,
+        seeds = [b"referral-vault", referrer.key().as_ref()],
+        bump
+    )]
+    pub referral_vault: Account<'info, ReferralVault>,
     #[account(mut)]
-    /// CHECK: destination address
-    pub fee_recipient: UncheckedAccount<'info>,
-    #[account(mut)] // <-- make writable for burn
-    pub mint: Account<'info, Mint>,
-    #[account(mut, seeds = [b"bonding-curve", mint.key().as_ref()], bump)]
-    pub bonding_curve: Account<'info, BondingCurve>,
+    pub referrer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ClaimReferralFees<'info> {
+    #[account(seeds = [b"global"], bump)]
+    pub global: Account<'info, Global>,
     #[account(
         mut,
-        seeds = [bonding_curve.key().as_ref(), token::ID.as_ref(), mint.key().as_ref()],
+        seeds = [b"referral-vault", referrer.key().as_ref()],
         bump,
-        seeds::program = associated_token::ID
+        has_one = referrer
     )]
-    pub associated_bonding_curve: Account<'info, TokenAccount>,
+    pub referral_vault: Account<'info, ReferralVault>,
     #[account(mut)]
-    pub associated_user: Account<'info, TokenAccount>,
+    pub referrer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct InitRoles<'info> {
+    #[account(seeds = [b"global"], bump)]
+    pub global: Account<'info, Global>,
+    #[account(init, payer = user, space = Roles::SIZE, seeds = [b"roles"], bump)]
+    pub roles: Account<'info, Roles>,
     #[account(mut)]
     pub user: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct UpdateRoles<'info> {
+    #[account(mut, seeds = [b"roles"], bump)]
+    pub roles: Account<'info, Roles>,
+    pub user: Signer<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SetPaused<'info> {
+    #[account(mut, seeds = [b"global"], bump)]
+    pub global: Account<'info, Global>,
+    #[account(seeds = [b"roles"], bump)]
+    pub roles: Account<'info, Roles>,
+    pub user: Signer<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SetCurveFrozen<'info> {
+    #[account(seeds = [b"roles"], bump)]
+    pub roles: Account<'info, Roles>,
+    pub mint: Account<'info, Mint>,
     #[account(
         mut,
-        seeds = [b"holder-stats", mint.key().as_ref(), user.key().as_ref()],
-        bump
+        seeds = [b"bonding-curve", mint.key().as_ref()],
+        bump,
+        constraint = BondingCurve::is_migrated(&bonding_curve.to_account_info()) @
+            HorseFunError::CurveNeedsMigration
     )]
-    pub holder_stats: Account<'info, HolderStats>,
-    pub system_program: Program<'info, System>,
-    pub associated_token_program: Program<'info, AssociatedToken>,
-    pub token_program: Program<'info, Token>,
+    pub bonding_curve: AccountLoader<'info, BondingCurve>,
+    pub user: Signer<'info>,
 }
 
 #[event_cpi]
 #[derive(Accounts)]
-pub struct InitHolderStats<'info> {
+pub struct UpdateLaunch<'info> {
+    #[account(seeds = [b"global"], bump)]
+    pub global: Account<'info, Global>,
     pub mint: Account<'info, Mint>,
     #[account(
-        init,
-        payer = user,
-        space = HolderStats::SIZE,
-        seeds = [b"holder-stats", mint.key().as_ref(), user.key().as_ref()],
-        bump
+        mut,
+        seeds = [b"bonding-curve", mint.key().as_ref()],
+        bump,
+        constraint = BondingCurve::is_migrated(&bonding_curve.to_account_info()) @
+            HorseFunError::CurveNeedsMigration
     )]
-    pub holder_stats: Account<'info, HolderStats>,
-    pub associated_user: Account<'info, TokenAccount>,
-    #[account(mut)]
+    pub bonding_curve: AccountLoader<'info, BondingCurve>,
     pub user: Signer<'info>,
-    pub system_program: Program<'info, System>,
 }
 
+impl<'info> UpdateLaunch<'info> {
+    /// Creator only, launch still pending and untouched by any trade.
+    pub fn validate(&self, now: i64) -> Result<()> {
+        let bonding_curve = self.bonding_curve.load()?;
+        require_keys_eq!(
+            self.user.key(),
+            bonding_curve.creator_wallet,
+            HorseFunError::UnauthorizedCreator
+        );
+        require!(bonding_curve.launch_cancelled == 0, HorseFunError::LaunchCancelled);
+        require!(now < bonding_curve.trading_starts_at, HorseFunError::LaunchAlreadyOpen);
+        require!(
+            bonding_curve.virtual_token_reserves ==
+                bonding_curve.initial_virtual_token_reserves,
+            HorseFunError::LaunchAlreadyOpen
+        );
+        Ok(())
+    }
+}
+
+#[event_cpi]
 #[derive(Accounts)]
-pub struct UpdateHolderStats<'info> {
+pub struct CommitOpeningAuction<'info> {
+    #[account(seeds = [b"global"], bump)]
+    pub global: Account<'info, Global>,
     pub mint: Account<'info, Mint>,
-    #[account(mut)]
-    pub associated_user: Account<'info, TokenAccount>,
     #[account(
         mut,
-        seeds = [b"holder-stats", mint.key().as_ref(), user.key().as_ref()],
+        seeds = [b"bonding-curve", mint.key().as_ref()],
+        bump,
+        constraint = BondingCurve::is_migrated(&bonding_curve.to_account_info()) @
+            HorseFunError::CurveNeedsMigration
+    )]
+    pub bonding_curve: AccountLoader<'info, BondingCurve>,
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = AuctionEscrow::SIZE,
+        seeds = [b"auction-escrow", mint.key().as_ref()],
         bump
     )]
-    pub holder_stats: Account<'info, HolderStats>,
+    pub auction_escrow: Account<'info, AuctionEscrow>,
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = AuctionBid::SIZE,
+        seeds = [b"auction-bid", mint.key().as_ref(), user.key().as_ref()],
+        bump
+    )]
+    pub auction_bid: Account<'info, AuctionBid>,
     #[account(mut)]
     pub user: Signer<'info>,
     pub system_program: Program<'info, System>,
 }
 
+#[event_cpi]
 #[derive(Accounts)]
-pub struct ClaimHolderFees<'info> {
+pub struct SettleOpeningAuction<'info> {
+    #[account(seeds = [b"global"], bump)]
+    pub global: Account<'info, Global>,
+    #[account(mut)]
+    /// CHECK: destination address
+    pub fee_recipient: UncheckedAccount<'info>,
     pub mint: Account<'info, Mint>,
-    #[account(mut, seeds = [b"bonding-curve", mint.key().as_ref()], bump)]
-    pub bonding_curve: Account<'info, BondingCurve>,
     #[account(
         mut,
-        seeds = [b"holder-stats", mint.key().as_ref(), user.key().as_ref()],
-        bump
+        seeds = [b"bonding-curve", mint.key().as_ref()],
+        bump,
+        constraint = BondingCurve::is_migrated(&bonding_curve.to_account_info()) @
+            HorseFunError::CurveNeedsMigration
     )]
-    pub holder_stats: Account<'info, HolderStats>,
-    #[account(mut)]
-    pub associated_user: Account<'info, TokenAccount>,
-    #[account(mut)]
+    pub bonding_curve: AccountLoader<'info, BondingCurve>,
+    #[account(mut, seeds = [b"auction-escrow", mint.key().as_ref()], bump)]
+    pub auction_escrow: Account<'info, AuctionEscrow>,
+    #[account(address = anchor_lang::solana_program::sysvar::slot_hashes::ID)]
+    /// CHECK: SlotHashes sysvar, read for the seat shuffle seed at `auction.seed_slot`
+    pub slot_hashes: UncheckedAccount<'info>,
     pub user: Signer<'info>,
-    pub system_program: Program<'info, System>,
 }
 
 #[event_cpi]
 #[derive(Accounts)]
-pub struct ClaimEarlyBirdRewards<'info> {
+pub struct ClaimOpeningAuction<'info> {
     #[account(seeds = [b"global"], bump)]
     pub global: Account<'info, Global>,
-    pub mint: Account<'info, Mint>,
-    #[account(mut, seeds = [b"bonding-curve", mint.key().as_ref()], bump)]
-    pub bonding_curve: Account<'info, BondingCurve>,
+    pub mint: Box<Account<'info, Mint>>,
+    #[account(
+        mut,
+        seeds = [b"bonding-curve", mint.key().as_ref()],
+        bump,
+        constraint = BondingCurve::is_migrated(&bonding_curve.to_account_info()) @
+            HorseFunError::CurveNeedsMigration
+    )]
+    pub bonding_curve: AccountLoader<'info, BondingCurve>,
+    #[account(
+        mut,
+        seeds = [bonding_curve.key().as_ref(), token::ID.as_ref(), mint.key().as_ref()],
+        bump,
+        seeds::program = associated_token::ID
+    )]
+    pub associated_bonding_curve: Box<Account<'info, TokenAccount>>,
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = mint,
+        associated_token::authority = user
+    )]
+    pub associated_user: Box<Account<'info, TokenAccount>>,
+    #[account(mut, seeds = [b"auction-escrow", mint.key().as_ref()], bump)]
+    pub auction_escrow: Account<'info, AuctionEscrow>,
     #[account(
         mut,
+        close = user,
+        seeds = [b"auction-bid", mint.key().as_ref(), user.key().as_ref()],
+        bump,
+        has_one = user
+    )]
+    pub auction_bid: Account<'info, AuctionBid>,
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = HolderStats::SIZE,
         seeds = [b"holder-stats", mint.key().as_ref(), user.key().as_ref()],
         bump
     )]
-    pub holder_stats: Account<'info, HolderStats>,
+    pub holder_stats: Box<Account<'info, HolderStats>>,
     #[account(mut)]
     pub user: Signer<'info>,
     pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
 }
 
 #[derive(Accounts)]
 pub struct Withdraw<'info> {
     #[account(seeds = [b"global"], bump)]
     pub global: Account<'info, Global>,
+    #[account(seeds = [b"roles"], bump)]
+    pub roles: Account<'info, Roles>,
     pub mint: Account<'info, Mint>,
-    #[account(mut, seeds = [b"bonding-curve", mint.key().as_ref()], bump)]
-    pub bonding_curve: Account<'info, BondingCurve>,
+    #[account(
+        mut,
+        seeds = [b"bonding-curve", mint.key().as_ref()],
+        bump,
+        constraint = BondingCurve::is_migrated(&bonding_curve.to_account_info()) @
+            HorseFunError::CurveNeedsMigration
+    )]
+    pub bonding_curve: AccountLoader<'info, BondingCurve>,
     #[account(
         mut,
         seeds = [bonding_curve.key().as_ref(), token::ID.as_ref(), mint.key().as_ref()],
@@ -2957,9 +7596,17 @@ pub struct Withdraw<'info> {
 
 #[derive(Accounts)]
 pub struct SendMessage<'info> {
+    #[account(seeds = [b"global"], bump)]
+    pub global: Account<'info, Global>,
     pub mint: Account<'info, Mint>,
-    #[account(mut, seeds = [b"bonding-curve", mint.key().as_ref()], bump)]
-    pub bonding_curve: Account<'info, BondingCurve>,
+    #[account(
+        mut,
+        seeds = [b"bonding-curve", mint.key().as_ref()],
+        bump,
+        constraint = BondingCurve::is_migrated(&bonding_curve.to_account_info()) @
+            HorseFunError::CurveNeedsMigration
+    )]
+    pub bonding_curve: AccountLoader<'info, BondingCurve>,
     #[account(
         init_if_needed,
         payer = user,
@@ -2976,9 +7623,19 @@ pub struct SendMessage<'info> {
 #[event_cpi]
 #[derive(Accounts)]
 pub struct ClaimDonations<'info> {
+    #[account(seeds = [b"global"], bump)]
+    pub global: Account<'info, Global>,
+    #[account(seeds = [b"roles"], bump)]
+    pub roles: Account<'info, Roles>,
     pub mint: Account<'info, Mint>,
-    #[account(mut, seeds = [b"bonding-curve", mint.key().as_ref()], bump)]
-    pub bonding_curve: Account<'info, BondingCurve>,
+    #[account(
+        mut,
+        seeds = [b"bonding-curve", mint.key().as_ref()],
+        bump,
+        constraint = BondingCurve::is_migrated(&bonding_curve.to_account_info()) @
+            HorseFunError::CurveNeedsMigration
+    )]
+    pub bonding_curve: AccountLoader<'info, BondingCurve>,
     #[account(
         mut,
         seeds = [b"message-list", mint.key().as_ref()],
@@ -2995,22 +7652,25 @@ pub struct ClaimDonations<'info> {
 impl<'info> ClaimDonations<'info> {
     pub fn validate(&self) -> Result<()> {
         // First check if it's the withdraw authority (platform failsafe)
-        if self.user.key() == config_feature::withdraw_authority::ID {
-            msg!("Authorized: Withdraw authority");
+        if self.user.key() == self.roles.withdrawer {
+            verbose_msg!("Authorized: Withdraw authority");
             return Ok(());
         }
 
+        let bonding_curve = self.bonding_curve.load()?;
         // Check if token was created with a streamer ID
-        if let Some(token_streamer_id) = self.bonding_curve.creator_streamer_id.as_ref() {
-            msg!("Token has streamer ID: {}", token_streamer_id);
+        if let Some(token_streamer_id) = bonding_curve.creator_streamer_id() {
+            verbose_msg!("Token has streamer ID: {}", token_streamer_id);
 
             // When streamer_id exists, ONLY a verified streamer can claim
             let streamer_account_info = self.streamer_identity.as_ref().ok_or_else(|| {
-                msg!("Unauthorized: No streamer identity provided for token with streamer ID");
+                verbose_msg!(
+                    "Unauthorized: No streamer identity provided for token with streamer ID"
+                );
                 HorseFunError::UnauthorizedDonationClaim
             })?;
 
-            msg!(
+            verbose_msg!(
                 "Attempting to deserialize streamer identity from account: {}",
                 streamer_account_info.key()
             );
@@ -3019,7 +7679,7 @@ impl<'info> ClaimDonations<'info> {
             let streamer_identity = StreamerIdentity::try_deserialize(
                 &mut &streamer_account_info.try_borrow_data()?[..]
             ).map_err(|_| {
-                msg!("Failed to deserialize streamer identity account");
+                verbose_msg!("Failed to deserialize streamer identity account");
                 HorseFunError::UnauthorizedDonationClaim
             })?;
 
@@ -3029,20 +7689,20 @@ impl<'info> ClaimDonations<'info> {
                 streamer_identity.verified &&
                 streamer_identity.streamer_id == *token_streamer_id
             {
-                msg!("Authorized: Verified streamer identity");
+                verbose_msg!("Authorized: Verified streamer identity");
                 return Ok(());
             }
 
-            msg!("Unauthorized: Invalid or unverified streamer identity");
+            verbose_msg!("Unauthorized: Invalid or unverified streamer identity");
             return Err(HorseFunError::UnauthorizedDonationClaim.into());
         } else {
-            msg!("Token has no streamer ID, checking creator wallet");
+            verbose_msg!("Token has no streamer ID, checking creator wallet");
             // No streamer ID, ONLY creator wallet can claim
             require!(
-                self.user.key() == self.bonding_curve.creator_wallet,
+                self.user.key() == bonding_curve.creator_wallet,
                 HorseFunError::UnauthorizedDonationClaim
             );
-            msg!("Authorized: Original creator wallet");
+            verbose_msg!("Authorized: Original creator wallet");
             return Ok(());
         }
     }
@@ -3050,3 +7710,264 @@ impl<'info> ClaimDonations<'info> {
 
 //Prediction Markets
 // --- ADD events ---
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const T0: u64 = 1_073_000_000_000_000;
+    const S0: u64 = 30_000_000_000;
+
+    fn curve(
+        kind: CurveKind,
+        virtual_token_reserves: u64,
+        virtual_sol_reserves: u64
+    ) -> BondingCurve {
+        // SAFETY: BondingCurve is a zero-copy (Pod) account, so all-zero is a valid value
+        let mut curve: BondingCurve = unsafe { std::mem::zeroed() };
+        curve.curve_kind = kind as u8;
+        curve.virtual_token_reserves = virtual_token_reserves;
+        curve.virtual_sol_reserves = virtual_sol_reserves;
+        curve.initial_virtual_token_reserves = T0;
+        curve.initial_virtual_sol_reserves = S0;
+        curve
+    }
+
+    /// A fresh curve and one with 400M tokens already sold, for every shape.
+    fn curves() -> Vec<BondingCurve> {
+        [CurveKind::ConstantProduct, CurveKind::Linear, CurveKind::Exponential]
+            .into_iter()
+            .flat_map(|kind| [curve(kind, T0, S0), curve(kind, T0 - 400_000_000_000_000, 2 * S0)])
+            .collect()
+    }
+
+    #[test]
+    fn tokens_for_budget_round_trips_through_buy_quote() {
+        for curve in curves() {
+            let math = curve.curve_kind().math();
+            for budget in [1_000, 1_000_000, 1_000_000_000, 10_000_000_000, 50_000_000_000] {
+                let tokens = math.tokens_for_budget(&curve, budget);
+                assert!(tokens < curve.virtual_token_reserves);
+
+                // Fits the budget, give or take the quote's round-up lamport
+                let cost = math.buy_quote_checked(&curve, tokens).unwrap();
+                assert!(cost <= budget + 1, "{:?}: {} > {}", curve.curve_kind(), cost, budget);
+
+                // ...and leaves at most 0.1% of the budget's tokens on the table
+                let more = tokens + tokens / 1_000 + 1;
+                assert!(
+                    math.buy_quote_checked(&curve, more).map_or(true, |cost| cost > budget),
+                    "{:?}: {} tokens still fit {}",
+                    curve.curve_kind(),
+                    more,
+                    budget
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn tokens_for_sell_output_round_trips_through_sell_quote() {
+        for curve in curves().into_iter().filter(|curve| curve.virtual_token_reserves < T0) {
+            let math = curve.curve_kind().math();
+            for sol_output in [1_000_000, 1_000_000_000, 10_000_000_000] {
+                let tokens = math.tokens_for_sell_output(&curve, sol_output).unwrap();
+
+                // Pays at least the requested output...
+                let output = math.sell_quote(&curve, tokens);
+                assert!(
+                    output >= sol_output,
+                    "{:?}: {} < {}",
+                    curve.curve_kind(),
+                    output,
+                    sol_output
+                );
+
+                // ...without selling more than 0.1% over what it takes
+                let fewer = tokens - tokens / 1_000 - 1;
+                assert!(math.sell_quote(&curve, fewer) < sol_output);
+            }
+        }
+    }
+
+    #[test]
+    fn sell_output_beyond_the_curve_is_rejected() {
+        for curve in curves() {
+            let math = curve.curve_kind().math();
+            assert_eq!(math.tokens_for_sell_output(&curve, curve.virtual_sol_reserves), None);
+        }
+        // Nothing sold yet, so a linear curve has nothing to pay out
+        let fresh = curve(CurveKind::Linear, T0, S0);
+        assert_eq!(LinearCurve.tokens_for_sell_output(&fresh, 1_000_000), None);
+    }
+
+    #[test]
+    fn spot_price_is_the_marginal_price() {
+        for curve in curves() {
+            let dt = 10_000_000_000;
+            let quoted = ((curve.buy_quote_checked(dt).unwrap() as u128) * PRICE_SCALE) /
+                (dt as u128);
+            let spot = curve.spot_price();
+            // Within 1 bps of the price a small buy actually pays
+            assert!(
+                quoted.abs_diff(spot) * 10_000 <= spot,
+                "{:?}: spot {} vs quoted {}",
+                curve.curve_kind(),
+                spot,
+                quoted
+            );
+        }
+    }
+
+    #[test]
+    fn launch_window_runs_from_the_first_buy() {
+        // A scheduled launch: created long before anyone can buy
+        let mut curve = curve(CurveKind::ConstantProduct, T0, S0);
+        curve.created_slot = 100;
+        assert_eq!(curve.launch_window_start(20_000), 20_000);
+
+        curve.trading_opened_slot = 20_000;
+        assert_eq!(curve.launch_window_start(25_000), 20_000);
+    }
+
+    #[test]
+    fn auction_seed_is_the_first_hash_at_or_after_the_seed_slot() {
+        // SlotHashes layout, newest first; slot 105 was skipped
+        let mut data = 3u64.to_le_bytes().to_vec();
+        for (slot, byte) in [(120u64, 3u8), (110, 2), (100, 1)] {
+            data.extend_from_slice(&slot.to_le_bytes());
+            data.extend_from_slice(&[byte; 32]);
+        }
+        let key = Pubkey::new_unique();
+        let mut lamports = 0;
+        let owner = Pubkey::default();
+        let info = AccountInfo::new(&key, false, false, &mut lamports, &mut data, &owner, false, 0);
+
+        assert_eq!(helpers::slot_hash_at(&info, 100).unwrap(), Some([1; 32]));
+        assert_eq!(helpers::slot_hash_at(&info, 105).unwrap(), Some([2; 32]));
+        // Not produced yet
+        assert!(helpers::slot_hash_at(&info, 121).is_err());
+        // Older than every entry, so it may have aged out
+        assert_eq!(helpers::slot_hash_at(&info, 90).unwrap(), None);
+    }
+
+    fn global() -> Global {
+        let mut global = Global::deserialize(&mut &vec![0u8; Global::SIZE][..]).unwrap();
+        global.fee_basis_points = 100;
+        global.creator_fee_share = 3_000;
+        global.platform_fee_share = 5_000;
+        global.treasury_fee_share = 1_000;
+        global.early_bird_fee_share = 1_000;
+        global.early_bird_cutoff = 50;
+        global.initial_virtual_token_reserves = T0;
+        global.initial_virtual_sol_reserves = S0;
+        global
+    }
+
+    fn read_legacy(mut data: Vec<u8>) -> LegacyBondingCurve {
+        let key = Pubkey::new_unique();
+        let mut lamports = 0;
+        let info = AccountInfo::new(&key, false, true, &mut lamports, &mut data, &ID, false, 0);
+        LegacyBondingCurve::read(&info).unwrap()
+    }
+
+    #[test]
+    fn baseline_curve_migrates_to_zero_copy() {
+        // The original borsh layout, allocated at its original size
+        const BASELINE_SIZE: usize = 8 + 8 * 6 + 1 + 32 + (4 + 50) + 8 * 12;
+        let creator = Pubkey::new_unique();
+        let mut data = BondingCurve::DISCRIMINATOR[..].to_vec();
+        for value in [T0 - 100, S0 + 5, 793_100_000_000_000, 5, 1_000_000_000_000_000, 100] {
+            data.extend_from_slice(&u64::to_le_bytes(value));
+        }
+        data.push(0); // complete
+        data.extend_from_slice(creator.as_ref());
+        data.push(1); // Some(streamer id)
+        data.extend_from_slice(&8u32.to_le_bytes());
+        data.extend_from_slice(b"streamer");
+        for value in 11..=22u64 {
+            data.extend_from_slice(&value.to_le_bytes());
+        }
+        assert!(data.len() <= BASELINE_SIZE);
+        data.resize(BASELINE_SIZE, 0);
+
+        let mut legacy = read_legacy(data);
+        assert_eq!(legacy.version, 0);
+        assert_eq!(legacy.virtual_token_reserves, T0 - 100);
+        assert_eq!(legacy.creator_streamer_id.as_deref(), Some("streamer"));
+        assert_eq!(legacy.early_bird_share_per_seat, 22);
+        assert_eq!(legacy.curve_kind, CurveKind::ConstantProduct);
+
+        let global = global();
+        legacy.migrate(&global);
+        let mut curve = curve(CurveKind::Linear, 0, 0);
+        legacy.write_to(&mut curve);
+
+        assert_eq!(curve.version, BondingCurve::VERSION);
+        assert_eq!(curve.curve_kind(), CurveKind::ConstantProduct);
+        assert_eq!(curve.virtual_token_reserves, T0 - 100);
+        assert_eq!(curve.virtual_sol_reserves, S0 + 5);
+        assert_eq!(curve.circulating_supply, 100);
+        assert_eq!(curve.complete, 0);
+        assert_eq!(curve.creator_wallet, creator);
+        assert_eq!(curve.creator_streamer_id(), Some("streamer"));
+        assert_eq!(curve.creator_fee_pool, 11);
+        assert_eq!(curve.total_buyers, 19);
+        assert_eq!(curve.early_bird_share_per_seat, 22);
+        // Baseline curves traded on Global's values, so the snapshot comes from there
+        assert_eq!(curve.fee_basis_points, global.fee_basis_points);
+        assert_eq!(curve.creator_fee_share, global.creator_fee_share);
+        assert_eq!(curve.early_bird_cutoff, global.early_bird_cutoff);
+        assert_eq!(curve.initial_virtual_token_reserves, T0);
+        assert_eq!(curve.initial_virtual_sol_reserves, S0);
+    }
+
+    #[test]
+    fn versioned_legacy_curve_keeps_its_snapshot() {
+        let mut legacy = LegacyBondingCurve::deserialize(
+            &mut &vec![0u8; LegacyBondingCurve::SIZE][..]
+        ).unwrap();
+        legacy.version = 1;
+        legacy.curve_kind = CurveKind::Exponential;
+        legacy.fee_basis_points = 50;
+        legacy.creator_fee_share = 10_000;
+        legacy.frozen = true;
+        legacy.price_ref = 7;
+
+        let mut data = BondingCurve::DISCRIMINATOR[..].to_vec();
+        legacy.serialize(&mut data).unwrap();
+
+        let mut legacy = read_legacy(data);
+        legacy.migrate(&global());
+        let mut curve = curve(CurveKind::ConstantProduct, 0, 0);
+        legacy.write_to(&mut curve);
+
+        assert_eq!(curve.curve_kind(), CurveKind::Exponential);
+        assert_eq!(curve.fee_basis_points, 50);
+        assert_eq!(curve.creator_fee_share, 10_000);
+        assert_eq!(curve.frozen, 1);
+        assert_eq!(u128::from_le_bytes(curve.price_ref), 7);
+        assert_eq!(curve.creator_streamer_id(), None);
+    }
+
+    #[test]
+    fn unmigrated_curves_are_rejected_before_loading() {
+        let key = Pubkey::new_unique();
+        let mut lamports = 0;
+        for (len, migrated) in [
+            (LegacyBondingCurve::SIZE, false),
+            (BondingCurve::SIZE - 1, false),
+            (BondingCurve::SIZE, true),
+        ] {
+            let mut data = vec![0u8; len];
+            let info = AccountInfo::new(&key, false, true, &mut lamports, &mut data, &ID, false, 0);
+            assert_eq!(BondingCurve::is_migrated(&info), migrated);
+        }
+
+        let mut curve = curve(CurveKind::ConstantProduct, T0, S0);
+        curve.version = BondingCurve::VERSION - 1;
+        curve.migrate(&global());
+        assert_eq!(curve.version, BondingCurve::VERSION);
+        assert_eq!(curve.virtual_token_reserves, T0);
+    }
+}